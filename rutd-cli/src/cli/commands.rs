@@ -1,8 +1,11 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
 use clap::{Parser, Subcommand};
 use clap_complete::ArgValueCompleter;
-use rutd_core::{MergeStrategy, Priority, SortOptions};
+use rutd_core::{DateRange, MergeStrategy, Priority, SortOptions};
 
-use super::FilterOptions;
+use super::{FilterOptions, filter::DATE_LONG_HELP};
 use crate::{completer, parser};
 
 /// RuTD - A Rust based To-Do list manager for your rushing to-dos
@@ -15,6 +18,21 @@ pub struct Cli {
     /// Verbosity level
     #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     pub verbose: u8,
+
+    /// Show the changes and commits a command would make without writing them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Workspace to work in, instead of the current one
+    #[arg(
+        short = 'W', long, global = true,
+        add = ArgValueCompleter::new(completer::complete_workspace)
+    )]
+    pub workspace: Option<String>,
+
+    /// Configuration file to use, instead of the default one
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -74,15 +92,29 @@ pub enum Commands {
         /// Show statistics (counts, total time spent)
         #[arg(long)]
         stats: bool,
+
+        /// List the tasks of every workspace
+        #[arg(long)]
+        all_workspaces: bool,
     },
-    /// Mark task as completed
+    /// Mark tasks as completed
     ///
-    /// Mark the task with the specified ID as completed
+    /// Mark the tasks with the specified IDs, or the tasks to do matching the
+    /// filters, as completed
     #[command(visible_aliases = ["d", "f"])]
     Done {
-        /// Task ID, if not specified, finish the active task
+        /// Task IDs, if neither IDs nor filters are specified, finish the
+        /// active task
         #[arg(add = ArgValueCompleter::new(completer::complete_id))]
-        id: Option<String>,
+        ids: Vec<String>,
+
+        /// Filter options
+        #[command(flatten)]
+        filter: FilterOptions,
+
+        /// Confirm without prompting when several tasks are affected
+        #[arg(long)]
+        force: bool,
     },
     /// Edit task description
     ///
@@ -109,18 +141,126 @@ pub enum Commands {
     /// Pause time tracking for the active task
     #[command(visible_aliases = ["p"])]
     Stop {},
-    /// Abort a task
+    /// Show the active task and sync status
     ///
-    /// Mark the task with the specified ID as aborted
+    /// Show the task being worked on, and whether local changes are pushed to
+    /// the remote repository as of the last fetch
+    Status {},
+    /// Abort tasks
+    ///
+    /// Mark the tasks with the specified IDs, or the tasks to do matching the
+    /// filters, as aborted
     #[command(visible_aliases = ["x", "c"])]
     Abort {
-        /// Task ID, if not specified, abort the active task
+        /// Task IDs, if neither IDs nor filters are specified, abort the
+        /// active task
+        #[arg(add = ArgValueCompleter::new(completer::complete_id))]
+        ids: Vec<String>,
+
+        /// Filter options
+        #[command(flatten)]
+        filter: FilterOptions,
+
+        /// Confirm without prompting when several tasks are affected
+        #[arg(long)]
+        force: bool,
+    },
+    /// Modify tasks
+    ///
+    /// Change the priority, scope or type of the tasks with the specified IDs,
+    /// or of the tasks matching the filters
+    #[command(visible_aliases = ["m"])]
+    Modify {
+        /// Task IDs
+        #[arg(add = ArgValueCompleter::new(completer::complete_id))]
+        ids: Vec<String>,
+
+        /// Filter options
+        #[command(flatten)]
+        filter: FilterOptions,
+
+        /// New task priority
+        #[arg(
+            long,
+            value_name = "PRIORITY",
+            add = ArgValueCompleter::new(completer::complete_priority)
+        )]
+        set_priority: Option<Priority>,
+
+        /// New task scope
+        #[arg(
+            long,
+            value_name = "SCOPE",
+            add = ArgValueCompleter::new(completer::complete_scope)
+        )]
+        set_scope: Option<String>,
+
+        /// New task type
+        #[arg(
+            long,
+            value_name = "TYPE",
+            add = ArgValueCompleter::new(completer::complete_type)
+        )]
+        set_type: Option<String>,
+
+        /// Confirm without prompting when several tasks are affected
+        #[arg(long)]
+        force: bool,
+    },
+    /// Snooze tasks
+    ///
+    /// Hide the tasks with the specified IDs, or the tasks matching the
+    /// filters, from the task list until the given date
+    Snooze {
+        /// Task IDs
+        #[arg(add = ArgValueCompleter::new(completer::complete_id))]
+        ids: Vec<String>,
+
+        /// Filter options
+        #[command(flatten)]
+        filter: FilterOptions,
+
+        /// Date to show the tasks again, e.g. "3d" for in three days
+        #[arg(
+            long,
+            value_name = "DATE",
+            default_value = "1d",
+            value_parser = parser::parse_date_ahead
+        )]
+        until: DateTime<Local>,
+
+        /// Show the tasks again now
+        #[arg(long, conflicts_with = "until")]
+        clear: bool,
+
+        /// Confirm without prompting when several tasks are affected
+        #[arg(long)]
+        force: bool,
+    },
+    /// Annotate tasks
+    ///
+    /// Add a note to the tasks with the specified IDs, or to the tasks
+    /// matching the filters
+    Annotate {
+        /// Task IDs
         #[arg(add = ArgValueCompleter::new(completer::complete_id))]
-        id: Option<String>,
+        ids: Vec<String>,
+
+        /// Filter options
+        #[command(flatten)]
+        filter: FilterOptions,
+
+        /// Text of the note
+        #[arg(short = 'm', long = "message", value_name = "TEXT")]
+        text: String,
+
+        /// Confirm without prompting when several tasks are affected
+        #[arg(long)]
+        force: bool,
     },
     /// Clean tasks
     ///
-    /// Remove tasks based on filters
+    /// Move tasks matching the filters to the trash
     #[command(visible_aliases = ["purge", "delete", "rm"])]
     Clean {
         /// Filter options
@@ -131,9 +271,82 @@ pub enum Commands {
         #[arg(long)]
         force: bool,
     },
+    /// Restore a deleted task
+    ///
+    /// Move the task with the specified ID back from the trash, or recover it
+    /// from the git history if it is no longer in the trash
+    #[command(visible_aliases = ["undelete"])]
+    Restore {
+        /// Task ID
+        #[arg(add = ArgValueCompleter::new(completer::complete_trashed_id))]
+        id: String,
+    },
+    /// Manage deleted tasks
+    ///
+    /// List or permanently remove tasks in the trash
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommands,
+    },
+    /// Show the history of a task
+    ///
+    /// List every change made to the task, including deleted ones, based on
+    /// the git history
+    #[command(visible_aliases = ["history"])]
+    Log {
+        /// Task ID
+        #[arg(add = ArgValueCompleter::new(completer::complete_id))]
+        id: String,
+    },
+    /// Show what changed across all tasks
+    ///
+    /// List the changes recorded in the git history within the date range,
+    /// grouped by action and scope, including changes to deleted tasks
+    Activity {
+        /// Date range of the changes, e.g. "1w-" for since last week
+        #[arg(
+            short, long,
+            value_name = "DATERANGE",
+            default_value = "1d-",
+            value_parser = parser::parse_date_range,
+            allow_hyphen_values = true,
+            long_help = DATE_LONG_HELP
+        )]
+        since: DateRange,
+    },
+    /// Undo the most recent changes
+    ///
+    /// Revert the last changes made by rutd using the git history, after
+    /// showing a preview of the affected tasks
+    #[command(visible_aliases = ["z"])]
+    Undo {
+        /// Number of changes to undo
+        #[arg(default_value_t = 1)]
+        count: usize,
+
+        /// Undo without prompting
+        #[arg(long)]
+        force: bool,
+    },
+    /// Upgrade task files written by older versions of rutd
+    ///
+    /// Rewrite the task files using the current schema version, in a single
+    /// commit. Older files can still be read without being upgraded.
+    Migrate {},
+    /// Commit pending changes
+    ///
+    /// Record all changes to tasks that are not committed yet in a single
+    /// commit, when auto-commit is disabled
+    Commit {
+        /// Commit message, generated from the changed tasks if not given
+        #[arg(short, long)]
+        message: Option<String>,
+    },
     /// Sync with remote repository
     ///
-    /// Fetch, pull and push changes to the remote repository
+    /// Fetch, pull and push changes to the remote repository. When conflicts
+    /// are left to resolve, edit the listed task files, then finish with
+    /// `--continue` or give up with `--abort`
     #[command(visible_aliases = ["y", "u"])]
     Sync {
         /// Conflict resolution preference when merging
@@ -143,6 +356,21 @@ pub enum Commands {
             add = ArgValueCompleter::new(completer::complete_merge_strategy)
         )]
         prefer: MergeStrategy,
+        /// Commit and push the merge once conflicts are resolved
+        ///
+        /// When rebasing, conflicts in the remaining local commits are
+        /// resolved with the `--prefer` strategy
+        #[arg(long, conflicts_with_all = ["abort", "conflicts", "status"])]
+        r#continue: bool,
+        /// Abort the merge and go back to the local tasks
+        #[arg(long, conflicts_with_all = ["conflicts", "status"])]
+        abort: bool,
+        /// List the tasks left to resolve
+        #[arg(long, conflicts_with = "status")]
+        conflicts: bool,
+        /// Show unpushed changes without contacting the remote
+        #[arg(long)]
+        status: bool,
     },
     /// Clone a remote repository
     ///
@@ -153,6 +381,28 @@ pub enum Commands {
         #[arg(value_hint = clap::ValueHint::Url)]
         url: String,
     },
+    /// Manage remote repositories
+    ///
+    /// Add a remote repository to tasks started locally, change its URL, or
+    /// show where tasks are synced
+    Remote {
+        #[command(subcommand)]
+        command: RemoteCommands,
+    },
+    /// Show where configuration, tasks and state are kept
+    ///
+    /// Show the configuration files in use, and the directories of the tasks
+    /// and of the active task and log
+    Paths {},
+    /// Manage workspaces
+    ///
+    /// List workspaces, add one with its own tasks directory, or switch to
+    /// another one
+    #[command(visible_aliases = ["ws"])]
+    Workspace {
+        #[command(subcommand)]
+        command: WorkspaceCommands,
+    },
     /// Manage configuration
     ///
     /// Get, set, or list configuration values
@@ -163,6 +413,86 @@ pub enum Commands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum RemoteCommands {
+    /// Add a remote repository
+    Add {
+        /// Remote repository URL
+        #[arg(value_hint = clap::ValueHint::Url)]
+        url: String,
+
+        /// Remote name, defaults to `git.remote` or "origin"
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Change the URL of a remote repository
+    SetUrl {
+        /// New remote repository URL
+        #[arg(value_hint = clap::ValueHint::Url)]
+        url: String,
+
+        /// Remote name, defaults to the remote tasks are synced with
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Show remote repositories and the branch tasks are synced with
+    Show,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WorkspaceCommands {
+    /// List workspaces
+    #[command(visible_aliases = ["l"])]
+    List,
+    /// Add a workspace
+    Add {
+        /// Workspace name
+        name: String,
+
+        /// Root directory of the workspace, holding its tasks and active task
+        #[arg(value_hint = clap::ValueHint::DirPath)]
+        root_dir: String,
+    },
+    /// Switch to another workspace
+    Use {
+        /// Workspace name, "default" for the workspace configured in the
+        /// `path` section
+        #[arg(add = ArgValueCompleter::new(completer::complete_workspace))]
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TrashCommands {
+    /// List tasks in the trash
+    #[command(visible_aliases = ["l"])]
+    List {
+        /// Sort options
+        #[arg(
+            short = 'o', long = "sort",
+            allow_hyphen_values = true,
+            value_parser = parser::parse_sort_options,
+            add = ArgValueCompleter::new(completer::complete_sort_options)
+        )]
+        sort: Option<SortOptions>,
+    },
+    /// Permanently remove tasks from the trash
+    Empty {
+        /// Only remove tasks deleted before this date (e.g., 30d, 2024/01)
+        #[arg(
+            long,
+            value_name = "DATE",
+            value_parser = parser::parse_date_cutoff,
+            allow_hyphen_values = true
+        )]
+        older_than: Option<DateTime<Local>>,
+
+        /// Confirm deletion without prompting
+        #[arg(long)]
+        force: bool,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ConfigCommands {
     /// Get a configuration value
@@ -192,6 +522,7 @@ pub enum ConfigCommands {
 #[cfg(test)]
 mod tests {
     use clap::CommandFactory;
+    use rutd_core::task::Filter;
 
     use super::*;
 
@@ -213,9 +544,11 @@ mod tests {
                 filter,
                 sort,
                 stats,
+                all_workspaces,
             } => {
                 assert!(sort.is_none());
                 assert!(!stats);
+                assert!(!all_workspaces);
                 // Default filter should be empty
                 assert!(filter.priority.is_none());
                 assert!(filter.task_scope.is_none());
@@ -269,8 +602,36 @@ mod tests {
 
         let cli = result.unwrap();
         match cli.command {
-            Commands::Done { id } => {
-                assert_eq!(id, Some("1a2b3c".to_string()));
+            Commands::Done { ids, force, .. } => {
+                assert_eq!(ids, vec!["1a2b3c"]);
+                assert!(!force);
+            }
+            _ => panic!("Should have parsed as done command"),
+        }
+    }
+
+    #[test]
+    fn test_done_command_with_filter() {
+        // Test the Done command on several tasks
+        let result = Cli::try_parse_from([
+            "rutd",
+            "done",
+            "--scope",
+            "legacy",
+            "--created",
+            "-2024",
+            "--force",
+        ]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Done { ids, filter, force } => {
+                assert!(ids.is_empty());
+                assert_eq!(filter.task_scope, Some("legacy".to_string()));
+                assert!(filter.creation_time.is_some());
+                assert!(force);
             }
             _ => panic!("Should have parsed as done command"),
         }
@@ -315,8 +676,8 @@ mod tests {
 
         let cli = result.unwrap();
         match cli.command {
-            Commands::Abort { id } => {
-                assert_eq!(id, Some("1a2b3c".to_string()));
+            Commands::Abort { ids, .. } => {
+                assert_eq!(ids, vec!["1a2b3c"]);
             }
             _ => panic!("Should have parsed as abort command"),
         }
@@ -331,13 +692,95 @@ mod tests {
 
         let cli = result.unwrap();
         match cli.command {
-            Commands::Abort { id } => {
-                assert_eq!(id, None);
+            Commands::Abort { ids, filter, .. } => {
+                assert!(ids.is_empty());
+                assert!(Filter::from(filter).is_empty());
             }
             _ => panic!("Should have parsed as abort command"),
         }
     }
 
+    #[test]
+    fn test_modify_command() {
+        let result = Cli::try_parse_from([
+            "rutd",
+            "modify",
+            "1a2b3c",
+            "4d5e6f",
+            "--set-priority",
+            "high",
+            "--set-scope",
+            "core",
+        ]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Modify {
+                ids,
+                set_priority,
+                set_scope,
+                set_type,
+                ..
+            } => {
+                assert_eq!(ids, vec!["1a2b3c", "4d5e6f"]);
+                assert_eq!(set_priority, Some(Priority::High));
+                assert_eq!(set_scope, Some("core".to_string()));
+                assert_eq!(set_type, None);
+            }
+            _ => panic!("Should have parsed as modify command"),
+        }
+    }
+
+    #[test]
+    fn test_snooze_command() {
+        let result = Cli::try_parse_from(["rutd", "snooze", "--scope", "legacy", "--until", "2w"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Snooze {
+                ids,
+                filter,
+                until,
+                clear,
+                ..
+            } => {
+                assert!(ids.is_empty());
+                assert_eq!(filter.task_scope, Some("legacy".to_string()));
+                assert!(until > Local::now());
+                assert!(!clear);
+            }
+            _ => panic!("Should have parsed as snooze command"),
+        }
+
+        // Clearing the snooze takes no date
+        assert!(Cli::try_parse_from(["rutd", "snooze", "1", "--clear"]).is_ok());
+        assert!(Cli::try_parse_from(["rutd", "snooze", "1", "--clear", "--until", "1d"]).is_err());
+    }
+
+    #[test]
+    fn test_annotate_command() {
+        let result =
+            Cli::try_parse_from(["rutd", "annotate", "1", "2", "-m", "Waiting for review"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Annotate { ids, text, .. } => {
+                assert_eq!(ids, vec!["1", "2"]);
+                assert_eq!(text, "Waiting for review");
+            }
+            _ => panic!("Should have parsed as annotate command"),
+        }
+
+        // The text is required
+        assert!(Cli::try_parse_from(["rutd", "annotate", "1"]).is_err());
+    }
+
     #[test]
     fn test_clean_command() {
         // Test the Clean command with force flag
@@ -359,6 +802,119 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_restore_command() {
+        let result = Cli::try_parse_from(["rutd", "restore", "1a2b3c"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Restore { id } => {
+                assert_eq!(id, "1a2b3c");
+            }
+            _ => panic!("Should have parsed as restore command"),
+        }
+    }
+
+    #[test]
+    fn test_trash_empty_command() {
+        let result = Cli::try_parse_from(["rutd", "trash", "empty", "--older-than", "30d"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Trash {
+                command: TrashCommands::Empty { older_than, force },
+            } => {
+                assert!(older_than.is_some_and(|date| date < Local::now()));
+                assert!(!force);
+            }
+            _ => panic!("Should have parsed as trash empty command"),
+        }
+    }
+
+    #[test]
+    fn test_log_command() {
+        let result = Cli::try_parse_from(["rutd", "log", "1a2b3c"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Log { id } => {
+                assert_eq!(id, "1a2b3c");
+            }
+            _ => panic!("Should have parsed as log command"),
+        }
+    }
+
+    #[test]
+    fn test_activity_command() {
+        let result = Cli::try_parse_from(["rutd", "activity"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Activity { since } => {
+                assert!(since.from.is_some_and(|date| date < Local::now()));
+                assert!(since.to.is_none());
+            }
+            _ => panic!("Should have parsed as activity command"),
+        }
+
+        let result = Cli::try_parse_from(["rutd", "activity", "--since", "1w"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Activity { since } => {
+                assert!(since.from.is_some());
+                assert!(since.to.is_some());
+            }
+            _ => panic!("Should have parsed as activity command"),
+        }
+    }
+
+    #[test]
+    fn test_migrate_command() {
+        let cli = Cli::try_parse_from(["rutd", "migrate", "--dry-run"]).unwrap();
+        assert!(matches!(cli.command, Commands::Migrate {}));
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn test_undo_command() {
+        let result = Cli::try_parse_from(["rutd", "undo"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Undo { count, force } => {
+                assert_eq!(count, 1);
+                assert!(!force);
+            }
+            _ => panic!("Should have parsed as undo command"),
+        }
+
+        let result = Cli::try_parse_from(["rutd", "undo", "3", "--force"]);
+
+        assert!(result.is_ok());
+
+        let cli = result.unwrap();
+        match cli.command {
+            Commands::Undo { count, force } => {
+                assert_eq!(count, 3);
+                assert!(force);
+            }
+            _ => panic!("Should have parsed as undo command"),
+        }
+    }
+
     #[test]
     fn test_sync_command() {
         // Test the Sync command with prefer option
@@ -368,11 +924,82 @@ mod tests {
 
         let cli = result.unwrap();
         match cli.command {
-            Commands::Sync { prefer } => {
+            Commands::Sync { prefer, .. } => {
                 assert_eq!(prefer, MergeStrategy::Local);
             }
             _ => panic!("Should have parsed as sync command"),
         }
+
+        // Continue or abort an interrupted sync
+        let cli = Cli::try_parse_from(["rutd", "sync", "--continue"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Sync {
+                r#continue: true,
+                abort: false,
+                ..
+            }
+        ));
+        assert!(Cli::try_parse_from(["rutd", "sync", "--continue", "--abort"]).is_err());
+
+        // Show the sync status
+        let cli = Cli::try_parse_from(["rutd", "sync", "--status"]).unwrap();
+        assert!(matches!(cli.command, Commands::Sync { status: true, .. }));
+        assert!(Cli::try_parse_from(["rutd", "sync", "--status", "--abort"]).is_err());
+    }
+
+    #[test]
+    fn test_commit_command() {
+        let cli = Cli::try_parse_from(["rutd", "commit"]).unwrap();
+        assert!(matches!(cli.command, Commands::Commit { message: None }));
+
+        let cli = Cli::try_parse_from(["rutd", "commit", "-m", "Busy day"]).unwrap();
+        match cli.command {
+            Commands::Commit { message } => assert_eq!(message.as_deref(), Some("Busy day")),
+            _ => panic!("Should have parsed as commit command"),
+        }
+    }
+
+    #[test]
+    fn test_remote_command() {
+        let cli =
+            Cli::try_parse_from(["rutd", "remote", "add", "https://example.com/repo.git"]).unwrap();
+        match cli.command {
+            Commands::Remote {
+                command: RemoteCommands::Add { url, name },
+            } => {
+                assert_eq!(url, "https://example.com/repo.git");
+                assert_eq!(name, None);
+            }
+            _ => panic!("Should have parsed as remote add command"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "rutd",
+            "remote",
+            "set-url",
+            "https://example.com/moved.git",
+            "--name",
+            "backup",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Remote {
+                command: RemoteCommands::SetUrl { url, name },
+            } => {
+                assert_eq!(url, "https://example.com/moved.git");
+                assert_eq!(name.as_deref(), Some("backup"));
+            }
+            _ => panic!("Should have parsed as remote set-url command"),
+        }
+
+        let cli = Cli::try_parse_from(["rutd", "remote", "show"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Remote {
+                command: RemoteCommands::Show
+            }
+        ));
     }
 
     #[test]
@@ -409,4 +1036,60 @@ mod tests {
         let cli = result.unwrap();
         assert_eq!(cli.verbose, 2);
     }
+
+    #[test]
+    fn test_dry_run_flag() {
+        let cli = Cli::try_parse_from(["rutd", "add", "Test task"]).unwrap();
+        assert!(!cli.dry_run);
+
+        // The flag is accepted after any subcommand
+        let cli = Cli::try_parse_from(["rutd", "done", "1", "--dry-run"]).unwrap();
+        assert!(cli.dry_run);
+        assert!(matches!(cli.command, Commands::Done { .. }));
+    }
+
+    #[test]
+    fn test_workspace_command() {
+        let cli = Cli::try_parse_from(["rutd", "workspace", "add", "team", "~/team"]).unwrap();
+        match cli.command {
+            Commands::Workspace {
+                command: WorkspaceCommands::Add { name, root_dir },
+            } => {
+                assert_eq!(name, "team");
+                assert_eq!(root_dir, "~/team");
+            }
+            _ => panic!("Should have parsed as workspace add command"),
+        }
+
+        let cli = Cli::try_parse_from(["rutd", "ws", "use", "team"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Workspace {
+                command: WorkspaceCommands::Use { name }
+            } if name == "team"
+        ));
+
+        // The workspace can be chosen for any command
+        let cli = Cli::try_parse_from(["rutd", "list", "-W", "team"]).unwrap();
+        assert_eq!(cli.workspace.as_deref(), Some("team"));
+        let cli = Cli::try_parse_from(["rutd", "list", "--all-workspaces"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::List {
+                all_workspaces: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_paths_command() {
+        let cli = Cli::try_parse_from(["rutd", "paths"]).unwrap();
+        assert!(matches!(cli.command, Commands::Paths {}));
+        assert!(cli.config_file.is_none());
+
+        // The configuration file can be chosen for any command
+        let cli = Cli::try_parse_from(["rutd", "list", "--config", "/tmp/rutd.toml"]).unwrap();
+        assert_eq!(cli.config_file, Some(PathBuf::from("/tmp/rutd.toml")));
+    }
 }
@@ -1,13 +1,28 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
+use chrono::{DateTime, Local};
 use colored::Colorize;
 use comfy_table::{Cell, CellAlignment, ContentArrangement, Row, Table};
-use dialoguer::{Confirm, Editor};
+use dialoguer::{Confirm, Editor, Select};
 use rutd_core::{
     display::Display,
-    task::{Priority, Task, TaskStatus},
+    git::{
+        changes::{ChangeSet, FileChange},
+        history::CommitInfo,
+        remote::{RemoteInfo, SyncStatus, SyncTarget},
+        repo::COMMITTER_NAME,
+    },
+    task::{
+        ActivityEntry, ActivityGroup, ChangeKind, Priority, Resolution, SHORT_ID_LENGTH, Side,
+        Task, TaskConflict, TaskEvent, TaskLogEntry, TaskStatus, UndoPlan, WorkingSet,
+        active_task::ActiveTask,
+    },
 };
+use similar::{ChangeTag, TextDiff};
 
 /// Responsible for handling all user interface output
 #[derive(Debug, Default)]
@@ -34,42 +49,32 @@ impl DisplayManager {
             TaskStatus::Aborted => cell.fg(comfy_table::Color::Red),
         }
     }
-}
 
-impl Display for DisplayManager {
-    fn confirm(&self, message: &str) -> Result<bool> {
-        let confirmed = Confirm::new().with_prompt(message).interact()?;
-        Ok(confirmed)
-    }
-    fn edit(&self, message: &str) -> Result<Option<String>> {
-        // FIXME: Handle cases when EDITOR is not set
-        Ok(Editor::new().edit(message)?)
-    }
-    fn show_success(&self, message: &str) {
-        println!("{} {}", "✓".green().bold(), message.green());
-    }
-    fn show_failure(&self, message: &str) {
-        eprintln!("{} {}", "✗".red().bold(), message.red());
-    }
-    /// Display the task list table
-    fn show_tasks_list(&self, tasks: &[Task]) {
+    /// Print the task list table, with the short numbers of working tasks
+    /// if a working set is provided
+    fn print_tasks_table(&self, tasks: &[Task], working_set: Option<&WorkingSet>) {
         if tasks.is_empty() {
             return;
         }
 
         // Create a table
+        let mut header = vec![
+            "ID",
+            "Description",
+            "Priority",
+            "Status",
+            "Scope",
+            "Type",
+            "Time Spent",
+            "Completed At",
+        ];
+        if working_set.is_some() {
+            header.insert(0, "#");
+        }
+
         let mut table = Table::new();
         table
-            .set_header(vec![
-                "ID",
-                "Description",
-                "Priority",
-                "Status",
-                "Scope",
-                "Type",
-                "Time Spent",
-                "Completed At",
-            ])
+            .set_header(header)
             .set_content_arrangement(ContentArrangement::Dynamic)
             .load_preset(comfy_table::presets::UTF8_FULL)
             .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
@@ -78,8 +83,16 @@ impl Display for DisplayManager {
         for task in tasks {
             let mut row = Row::new();
 
+            // Short number of working tasks
+            if let Some(working_set) = working_set {
+                let number = working_set
+                    .number(&task.id)
+                    .map_or("-".to_string(), |number| number.to_string());
+                row.add_cell(Cell::new(number).set_alignment(CellAlignment::Right));
+            }
+
             // Use the first 8 characters of the task ID, should be unique
-            row.add_cell(Cell::new(&task.id[0..8]));
+            row.add_cell(Cell::new(&task.id[..SHORT_ID_LENGTH]));
 
             // Description
             row.add_cell(Cell::new(&task.description));
@@ -112,9 +125,8 @@ impl Display for DisplayManager {
             row.add_cell(Cell::new(time_spent).set_alignment(CellAlignment::Right));
 
             // Completed at
-            let complete_at = task.completed_at.as_ref().map_or("-".to_string(), |t| {
-                let date = t.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
-                date.format("%Y-%m-%d %H:%M:%S").to_string()
+            let complete_at = task.completed_at.map_or("-".to_string(), |t| {
+                t.format("%Y-%m-%d %H:%M:%S").to_string()
             });
             row.add_cell(Cell::new(complete_at));
 
@@ -124,6 +136,27 @@ impl Display for DisplayManager {
         // Finalize the table and print it
         println!("{table}");
     }
+}
+
+impl Display for DisplayManager {
+    fn confirm(&self, message: &str) -> Result<bool> {
+        let confirmed = Confirm::new().with_prompt(message).interact()?;
+        Ok(confirmed)
+    }
+    fn edit(&self, message: &str) -> Result<Option<String>> {
+        // FIXME: Handle cases when EDITOR is not set
+        Ok(Editor::new().edit(message)?)
+    }
+    fn show_success(&self, message: &str) {
+        println!("{} {}", "✓".green().bold(), message.green());
+    }
+    fn show_failure(&self, message: &str) {
+        eprintln!("{} {}", "✗".red().bold(), message.red());
+    }
+    /// Display the task list table
+    fn show_tasks_list(&self, tasks: &[Task]) {
+        self.print_tasks_table(tasks, None);
+    }
     /// Display task statistics
     fn show_task_stats(&self, tasks: &[Task]) {
         let mut stats_table = Table::new();
@@ -185,14 +218,22 @@ impl Display for DisplayManager {
             table.add_row(vec!["Type", task_type]);
         }
 
-        table.add_row(vec!["Created at", &task.created_at]);
+        table.add_row(vec!["Created at", &task.created_at.to_rfc3339()]);
 
         if let Some(updated_at) = &task.updated_at {
-            table.add_row(vec!["Updated at", updated_at]);
+            table.add_row(vec!["Updated at", &updated_at.to_rfc3339()]);
         }
 
         if let Some(completed_at) = &task.completed_at {
-            table.add_row(vec!["Completed at", completed_at]);
+            table.add_row(vec!["Completed at", &completed_at.to_rfc3339()]);
+        }
+
+        if let Some(deleted_at) = &task.deleted_at {
+            table.add_row(vec!["Deleted at", &deleted_at.to_rfc3339()]);
+        }
+
+        if let Some(snoozed_until) = task.snoozed_until.filter(|_| task.is_snoozed()) {
+            table.add_row(vec!["Snoozed until", &snoozed_until.to_rfc3339()]);
         }
 
         if let Some(time_spent) = task.time_spent {
@@ -205,11 +246,553 @@ impl Display for DisplayManager {
             ]);
         }
 
+        for annotation in &task.annotations {
+            table.add_row(vec![
+                &annotation.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                &annotation.text,
+            ]);
+        }
+
         println!("{table}");
     }
+
+    /// Let the user resolve a conflicting task
+    ///
+    /// The user can keep either version, merge them field by field or edit
+    /// the merged file, until a valid task is given or the conflict is
+    /// skipped.
+    fn resolve_conflict(&self, conflict: &TaskConflict) -> Result<Option<Resolution>> {
+        self.print_conflict(conflict, conflict.path());
+
+        let can_merge = conflict.merge(&[]).is_some();
+        let mut items = vec!["Keep local version", "Keep remote version"];
+        if can_merge {
+            items.push("Merge field by field");
+        }
+        items.extend(["Edit merged file", "Skip, resolve manually later"]);
+
+        loop {
+            let Some(selection) = Select::new()
+                .with_prompt("Resolve conflict")
+                .items(&items)
+                .default(0)
+                .interact_opt()?
+            else {
+                return Ok(None);
+            };
+
+            let resolution = match items[selection] {
+                "Keep local version" => Resolution::Side(Side::Ours),
+                "Keep remote version" => Resolution::Side(Side::Theirs),
+                "Merge field by field" => match self.select_fields(conflict)? {
+                    Some(sides) => Resolution::Fields(sides),
+                    None => continue,
+                },
+                "Edit merged file" => {
+                    // Start from the merged task, or the local file if it is not a task
+                    let content = if can_merge {
+                        conflict.resolve(&Resolution::Fields(Vec::new()))?
+                    } else {
+                        conflict
+                            .file
+                            .ours
+                            .clone()
+                            .or_else(|| conflict.file.theirs.clone())
+                    };
+                    let Some(content) = Editor::new()
+                        .extension(".toml")
+                        .edit(&content.unwrap_or_default())?
+                    else {
+                        continue;
+                    };
+                    Resolution::Content(content)
+                }
+                _ => return Ok(None),
+            };
+
+            // Ask again until the result is a valid task
+            match conflict.resolve(&resolution) {
+                Ok(_) => return Ok(Some(resolution)),
+                Err(e) => self.show_failure(&format!("Invalid resolution: {e}")),
+            }
+        }
+    }
 }
 
 impl DisplayManager {
+    /// Display the task list table with the short numbers of working tasks
+    pub fn show_numbered_tasks_list(&self, tasks: &[Task], working_set: &WorkingSet) {
+        self.print_tasks_table(tasks, Some(working_set));
+    }
+
+    /// Display the tasks of a workspace, without short numbers
+    pub fn show_workspace_tasks(&self, name: &str, tasks: &[Task]) {
+        println!("{}", name.bold());
+        self.print_tasks_table(tasks, None);
+    }
+
+    /// Display workspaces and their root directories, marking the current one
+    pub fn show_workspaces(&self, workspaces: &[(String, PathBuf)], current: &str) {
+        let mut table = Table::new();
+        table
+            .load_preset(comfy_table::presets::UTF8_FULL)
+            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
+
+        table.set_header(vec!["", "Name", "Root directory"]);
+        for (name, root_dir) in workspaces {
+            let marker = if name == current { "*" } else { "" };
+            table.add_row(vec![
+                Cell::new(marker).fg(comfy_table::Color::Green),
+                Cell::new(name),
+                Cell::new(root_dir.display()),
+            ]);
+        }
+
+        println!("{table}");
+    }
+
+    /// Display resolved locations, with a placeholder for missing ones
+    pub fn show_paths(&self, paths: &[(&str, Option<PathBuf>)]) {
+        let mut table = Table::new();
+        table
+            .load_preset(comfy_table::presets::UTF8_FULL)
+            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
+
+        table.set_header(vec!["Name", "Path"]);
+        for (name, path) in paths {
+            let path = path
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |path| path.display().to_string());
+            table.add_row(vec![Cell::new(name), Cell::new(path)]);
+        }
+
+        println!("{table}");
+    }
+
+    /// Display a warning message
+    pub fn show_warning(&self, message: &str) {
+        eprintln!("{} {}", "!".yellow().bold(), message.yellow());
+    }
+
+    /// Describe a task in a selection list
+    fn format_candidate(&self, task: &Task) -> String {
+        format!(
+            "{}  {} [{}, {}]",
+            task.id,
+            task.description.lines().next().unwrap_or_default(),
+            task.status,
+            task.scope.as_deref().unwrap_or("-")
+        )
+    }
+
+    /// Let the user choose one of the tasks matching an ambiguous ID
+    ///
+    /// Returns the index of the chosen task, or `None` if the selection is
+    /// cancelled.
+    pub fn select_task(&self, id: &str, tasks: &[Task]) -> Result<Option<usize>> {
+        let items = tasks
+            .iter()
+            .map(|task| self.format_candidate(task))
+            .collect::<Vec<_>>();
+        let selection = Select::new()
+            .with_prompt(format!(
+                "Multiple tasks found with ID starting with {id}, choose one"
+            ))
+            .items(&items)
+            .default(0)
+            .interact_opt()?;
+        Ok(selection)
+    }
+
+    /// Display the commits and task changes of an undo plan
+    pub fn show_undo_preview(&self, plan: &UndoPlan) {
+        println!("Changes to undo:");
+        for commit in &plan.commits {
+            let pushed = if commit.pushed { " (pushed)" } else { "" };
+            println!(
+                "  {} {} {}{pushed}",
+                commit.id.to_string()[0..8].yellow(),
+                commit.time.format("%Y-%m-%d %H:%M:%S"),
+                commit.summary()
+            );
+        }
+
+        if !plan.changes.is_empty() {
+            let mut table = Table::new();
+            table
+                .set_header(vec!["ID", "Description", "Change"])
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .load_preset(comfy_table::presets::UTF8_FULL)
+                .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
+
+            for change in &plan.changes {
+                let task = change.task();
+                let action = match (change.kind(), change.in_trash) {
+                    (ChangeKind::Added, false) => "restore",
+                    (ChangeKind::Modified, false) => "revert",
+                    (ChangeKind::Deleted, false) => "remove",
+                    (ChangeKind::Added, true) => "restore to trash",
+                    (ChangeKind::Modified, true) => "revert in trash",
+                    (ChangeKind::Deleted, true) => "remove from trash",
+                };
+                table.add_row(vec![
+                    Cell::new(&task.id[..SHORT_ID_LENGTH]),
+                    Cell::new(&task.description),
+                    Cell::new(action),
+                ]);
+            }
+
+            println!("{table}");
+        }
+
+        if let Some(active) = &plan.active_task {
+            println!(
+                "Task {} will be active again",
+                &active.task_id[..SHORT_ID_LENGTH]
+            );
+        }
+    }
+
+    /// Describe an event in the history of a task
+    fn format_task_event(&self, event: &TaskEvent) -> String {
+        let first_line = |text: &str| text.lines().next().unwrap_or_default().to_string();
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+        match event {
+            TaskEvent::Created => "created".green().to_string(),
+            TaskEvent::Deleted => "deleted".red().to_string(),
+            TaskEvent::Restored => "restored".green().to_string(),
+            TaskEvent::Purged => "permanently removed".red().to_string(),
+            TaskEvent::Status(old, new) => format!("status: {old} -> {new}"),
+            TaskEvent::Priority(old, new) => format!("priority: {old} -> {new}"),
+            TaskEvent::Description(old, new) => format!(
+                "description: \"{}\" -> \"{}\"",
+                first_line(old),
+                first_line(new)
+            ),
+            TaskEvent::Scope(old, new) => format!("scope: {} -> {}", or_none(old), or_none(new)),
+            TaskEvent::TaskType(old, new) => {
+                format!("type: {} -> {}", or_none(old), or_none(new))
+            }
+            TaskEvent::TimeAdded(seconds) => {
+                let sign = if *seconds < 0 { "-" } else { "+" };
+                let seconds = seconds.unsigned_abs();
+                format!(
+                    "time spent: {sign}{}h {}m {}s",
+                    seconds / 3600,
+                    (seconds % 3600) / 60,
+                    seconds % 60
+                )
+            }
+        }
+    }
+
+    /// Display the history of a task
+    pub fn show_task_log(&self, entries: &[TaskLogEntry]) {
+        if let Some(task) = entries.iter().rev().find_map(|entry| entry.task.as_ref()) {
+            println!(
+                "History of task {}: {}",
+                &task.id[..SHORT_ID_LENGTH],
+                task.description
+            );
+        }
+
+        for entry in entries {
+            println!(
+                "{} {}{}",
+                entry
+                    .commit
+                    .time
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+                    .yellow(),
+                entry.commit.summary().dimmed(),
+                Self::format_author(&entry.commit)
+            );
+            for event in &entry.events {
+                println!("  {}", self.format_task_event(event));
+            }
+        }
+    }
+
+    /// Format the author of a commit, unless it was authored by rutd itself
+    fn format_author(commit: &CommitInfo) -> String {
+        if commit.author.is_empty() || commit.author == COMMITTER_NAME {
+            return String::new();
+        }
+        format!(" {}", format!("by {}", commit.author).cyan())
+    }
+
+    /// Display the activity log grouped by action and scope
+    pub fn show_activity(&self, groups: &[ActivityGroup]) {
+        if groups.is_empty() {
+            println!("No activity found.");
+            return;
+        }
+
+        for group in groups {
+            println!(
+                "{} ({})",
+                group.action.bold(),
+                group.scope.as_deref().unwrap_or("-")
+            );
+            for entry in &group.entries {
+                println!(
+                    "  {} {}{}",
+                    entry
+                        .commit
+                        .time
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                        .yellow(),
+                    entry.message.description,
+                    Self::format_author(&entry.commit)
+                );
+                for task in &entry.tasks {
+                    let id = task.id.get(0..8).unwrap_or(&task.id);
+                    let description = task.description.as_deref().unwrap_or("-");
+                    let first_line = description.lines().next().unwrap_or_default();
+                    println!("    {id} {first_line}");
+                }
+            }
+        }
+    }
+
+    /// Display remote repositories and the branch tasks are synced with
+    pub fn show_remotes(&self, remotes: &[RemoteInfo], target: Option<&SyncTarget>) {
+        if remotes.is_empty() {
+            println!("No remote repository configured.");
+            return;
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(comfy_table::presets::UTF8_FULL)
+            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
+
+        table.set_header(vec!["Remote", "URL"]);
+        for remote in remotes {
+            table.add_row(vec![&remote.name, remote.url.as_deref().unwrap_or("-")]);
+        }
+        println!("{table}");
+
+        if let Some(target) = target {
+            println!(
+                "Syncing '{}' with '{}/{}'",
+                target.local_branch.bold(),
+                target.remote.bold(),
+                target.remote_branch.bold()
+            );
+        }
+    }
+
+    /// Fields shown when comparing the versions of a conflicting task
+    const CONFLICT_FIELDS: [&str; 7] = [
+        "description",
+        "status",
+        "priority",
+        "scope",
+        "type",
+        "time spent",
+        "updated at",
+    ];
+
+    /// Format the fields of a task shown when comparing its versions
+    fn format_conflict_fields(task: &Task) -> [String; 7] {
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        let time_spent = task.time_spent.unwrap_or_default();
+        [
+            task.description.clone(),
+            task.status.to_string(),
+            task.priority.to_string(),
+            or_none(&task.scope),
+            or_none(&task.task_type),
+            format!(
+                "{}h {}m {}s",
+                time_spent / 3600,
+                (time_spent % 3600) / 60,
+                time_spent % 60
+            ),
+            task.updated_at
+                .map_or_else(|| "-".to_string(), |time| time.to_rfc3339()),
+        ]
+    }
+
+    /// Display the versions of a conflicting task side by side
+    ///
+    /// Fields changed differently on both sides are shown in red, other
+    /// differences in yellow. The common ancestor is shown if known.
+    fn print_conflict(&self, conflict: &TaskConflict, path: &Path) {
+        println!(
+            "{} {}",
+            path.display().to_string().bold(),
+            conflict.description().unwrap_or_default()
+        );
+
+        let mut header = vec!["Field", "Local", "Remote"];
+        if conflict.base.is_some() {
+            header.push("Base");
+        }
+        let mut table = Table::new();
+        table
+            .set_header(header)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .load_preset(comfy_table::presets::UTF8_FULL)
+            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
+
+        let base = conflict.base.as_ref().map(Self::format_conflict_fields);
+        let ours = conflict.ours.as_ref().map(Self::format_conflict_fields);
+        let theirs = conflict.theirs.as_ref().map(Self::format_conflict_fields);
+        for (index, field) in Self::CONFLICT_FIELDS.into_iter().enumerate() {
+            let local = ours.as_ref().map_or("(deleted)", |values| &values[index]);
+            let remote = theirs.as_ref().map_or("(deleted)", |values| &values[index]);
+            let name = if conflict.fields.iter().any(|f| f.field == field) {
+                Cell::new(field).fg(comfy_table::Color::Red)
+            } else if local != remote {
+                Cell::new(field).fg(comfy_table::Color::Yellow)
+            } else {
+                Cell::new(field)
+            };
+            let mut row = vec![name, Cell::new(local), Cell::new(remote)];
+            if let Some(base) = &base {
+                row.push(Cell::new(&base[index]));
+            }
+            table.add_row(row);
+        }
+
+        println!("{table}");
+    }
+
+    /// Display the tasks left conflicting by a sync, with both versions side
+    /// by side
+    pub fn show_sync_conflicts(&self, conflicts: &[TaskConflict], task_dir: &Path) {
+        if conflicts.is_empty() {
+            println!("No conflicts to resolve.");
+            return;
+        }
+
+        for conflict in conflicts {
+            self.print_conflict(conflict, &task_dir.join(conflict.path()));
+        }
+
+        println!(
+            "Edit the task files above, then run 'sync --continue', or 'sync --abort' to give up"
+        );
+    }
+
+    /// Display the task being worked on
+    pub fn show_active_task(&self, active: Option<&(Task, ActiveTask)>) {
+        let Some((task, active_task)) = active else {
+            println!("No active task.");
+            return;
+        };
+
+        let id = task.id.get(0..8).unwrap_or(&task.id);
+        let first_line = task.description.lines().next().unwrap_or_default();
+        let since = DateTime::parse_from_rfc3339(&active_task.started_at)
+            .map(|started_at| {
+                started_at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| active_task.started_at.clone());
+        println!(
+            "Working on {} {} since {}",
+            id.bold(),
+            first_line,
+            since.yellow()
+        );
+    }
+
+    /// Display whether local changes are pushed to the remote branch
+    pub fn show_sync_status(&self, status: Option<&SyncStatus>, unpushed: &[ActivityEntry]) {
+        let Some(status) = status else {
+            println!("No remote repository configured.");
+            return;
+        };
+
+        let remote_branch = format!("{}/{}", status.target.remote, status.target.remote_branch);
+        if status.in_progress {
+            self.show_warning("A sync is waiting for conflicts to be resolved");
+        }
+        if !status.tracked {
+            println!(
+                "Branch '{}' has not been pushed to {} yet",
+                status.target.local_branch,
+                remote_branch.bold()
+            );
+        } else if status.ahead == 0 && status.behind == 0 {
+            println!("Up to date with {}", remote_branch.bold());
+        } else {
+            println!(
+                "{} ahead, {} behind {}",
+                status.ahead.to_string().green(),
+                status.behind.to_string().red(),
+                remote_branch.bold()
+            );
+        }
+        if status.uncommitted > 0 {
+            println!("{} task files not committed", status.uncommitted);
+        }
+        match status.last_fetch {
+            Some(time) => println!(
+                "Last fetched at {}",
+                time.format("%Y-%m-%d %H:%M:%S").to_string().yellow()
+            ),
+            None => println!("Never fetched"),
+        }
+
+        if unpushed.is_empty() {
+            return;
+        }
+        println!("{}", "Unpushed changes:".bold());
+        for entry in unpushed {
+            println!(
+                "  {} {}",
+                entry
+                    .commit
+                    .time
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+                    .yellow(),
+                entry.message.description
+            );
+            for task in &entry.tasks {
+                let id = task.id.get(0..8).unwrap_or(&task.id);
+                let description = task.description.as_deref().unwrap_or("-");
+                let first_line = description.lines().next().unwrap_or_default();
+                println!("    {id} {first_line}");
+            }
+        }
+    }
+
+    /// Let the user pick a side for each conflicting field
+    ///
+    /// Returns `None` if the selection is cancelled.
+    fn select_fields(&self, conflict: &TaskConflict) -> Result<Option<Vec<Side>>> {
+        let mut sides = Vec::new();
+        for field in &conflict.fields {
+            let items = [
+                format!("local: {}", field.ours),
+                format!("remote: {}", field.theirs),
+            ];
+            let Some(selection) = Select::new()
+                .with_prompt(format!("Choose the {}", field.field))
+                .items(&items)
+                .default(0)
+                .interact_opt()?
+            else {
+                return Ok(None);
+            };
+            sides.push(if selection == 0 {
+                Side::Ours
+            } else {
+                Side::Theirs
+            });
+        }
+        Ok(Some(sides))
+    }
+
     /// Display configuration values
     pub fn show_config_values(&self, values: &BTreeMap<String, String>) {
         let mut table = Table::new();
@@ -224,6 +807,57 @@ impl DisplayManager {
 
         println!("{table}");
     }
+
+    /// Display the commits and file changes recorded in a dry run
+    pub fn show_dry_run(&self, changes: &[ChangeSet]) {
+        if changes.is_empty() {
+            println!("{}", "Dry run: no changes".yellow());
+            return;
+        }
+
+        for change_set in changes {
+            println!("{}", "Dry run: would commit".yellow().bold());
+            for line in change_set.message.lines() {
+                println!("{}", format!("    {line}").trim_end());
+            }
+            for file in &change_set.files {
+                print!("{}", Self::format_file_diff(file));
+            }
+            println!();
+        }
+    }
+
+    /// Format the change to a file as a unified diff
+    fn format_file_diff(file: &FileChange) -> String {
+        let path = file.path.display();
+        let old_header = file
+            .old
+            .as_ref()
+            .map_or_else(|| "/dev/null".to_string(), |_| format!("a/{path}"));
+        let new_header = file
+            .new
+            .as_ref()
+            .map_or_else(|| "/dev/null".to_string(), |_| format!("b/{path}"));
+        let old = file.old.as_deref().unwrap_or_default();
+        let new = file.new.as_deref().unwrap_or_default();
+
+        let diff = TextDiff::from_lines(old, new);
+        let mut output = format!("--- {old_header}\n+++ {new_header}\n");
+        for hunk in diff.unified_diff().iter_hunks() {
+            output.push_str(&format!("{}\n", hunk.header().to_string().cyan()));
+            for change in hunk.iter_changes() {
+                let value = change.value();
+                let line = value.strip_suffix('\n').unwrap_or(value);
+                let line = match change.tag() {
+                    ChangeTag::Delete => format!("-{line}").red(),
+                    ChangeTag::Insert => format!("+{line}").green(),
+                    ChangeTag::Equal => format!(" {line}").normal(),
+                };
+                output.push_str(&format!("{line}\n"));
+            }
+        }
+        output
+    }
 }
 
 #[cfg(test)]
@@ -240,14 +874,17 @@ mod tests {
             scope: Some("test-scope".to_string()),
             task_type: Some("test-type".to_string()),
             status,
-            created_at: Local::now().to_rfc3339(),
-            updated_at: Some(Local::now().to_rfc3339()),
+            created_at: Local::now().fixed_offset(),
+            updated_at: Some(Local::now().fixed_offset()),
             completed_at: if status == TaskStatus::Done || status == TaskStatus::Aborted {
-                Some(Local::now().to_rfc3339())
+                Some(Local::now().fixed_offset())
             } else {
                 None
             },
             time_spent: Some(3600), // 1 hour in seconds
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         }
     }
 
@@ -314,10 +951,55 @@ mod tests {
 
         // This is a visual test, so we just ensure it doesn't panic
         display.show_tasks_list(&tasks);
+        display.show_numbered_tasks_list(&tasks, &WorkingSet::from_tasks(&tasks));
         // For empty tasks
         display.show_tasks_list(&[]);
     }
 
+    #[test]
+    fn test_format_file_diff() {
+        colored::control::set_override(false);
+
+        let file = FileChange {
+            path: "task.toml".into(),
+            old: Some("id = \"task\"\nstatus = \"todo\"\n".to_string()),
+            new: Some("id = \"task\"\nstatus = \"done\"\n".to_string()),
+        };
+        assert_eq!(
+            DisplayManager::format_file_diff(&file),
+            "--- a/task.toml\n+++ b/task.toml\n@@ -1,2 +1,2 @@\n id = \"task\"\n-status = \"todo\"\n+status = \"done\"\n"
+        );
+
+        let file = FileChange {
+            path: "task.toml".into(),
+            old: None,
+            new: Some("id = \"task\"\n".to_string()),
+        };
+        assert!(
+            DisplayManager::format_file_diff(&file)
+                .starts_with("--- /dev/null\n+++ b/task.toml\n@@ -0,0 +1 @@\n+id")
+        );
+    }
+
+    #[test]
+    fn test_format_candidate() {
+        let display = DisplayManager;
+
+        let task = create_test_task("task-123", TaskStatus::Done);
+        assert_eq!(
+            display.format_candidate(&task),
+            "task-123  Test task task-123 [done, test-scope]"
+        );
+
+        let mut task = create_test_task("task-456", TaskStatus::Todo);
+        task.description = "First line\nSecond line".to_string();
+        task.scope = None;
+        assert_eq!(
+            display.format_candidate(&task),
+            "task-456  First line [todo, -]"
+        );
+    }
+
     #[test]
     fn test_show_task_detail() {
         let display = DisplayManager;
@@ -336,10 +1018,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         display.show_task_detail(&minimal_task);
@@ -4,7 +4,7 @@ use rutd_core::task::{DateRange, Filter, Priority, TaskStatus};
 
 use crate::{completer, parser};
 
-const DATE_LONG_HELP: &str = "
+pub(super) const DATE_LONG_HELP: &str = "
 Date range format: [<date>]..[<date>] or <date>
 
 <date> format:
@@ -86,6 +86,10 @@ pub struct FilterOptions {
     /// Enable fuzzy matching for description
     #[arg(short, long, value_name = "DESCRIPTION")]
     pub fuzzy: Option<String>,
+
+    /// Only match snoozed tasks, which are hidden from the list otherwise
+    #[arg(long)]
+    pub snoozed: bool,
 }
 
 // Implement From trait to convert CliFilterOptions to FilterOptions
@@ -100,6 +104,7 @@ impl From<FilterOptions> for Filter {
             update_time: cli_filter.update_time,
             completion_time: cli_filter.completion_time,
             fuzzy: cli_filter.fuzzy,
+            snoozed: cli_filter.snoozed.then_some(true),
         }
     }
 }
@@ -136,6 +141,7 @@ mod tests {
                 to: Some(create_date(2023, 12, 31)),
             }),
             fuzzy: Some("test-description".to_string()),
+            snoozed: true,
         };
 
         // Convert to Filter
@@ -175,6 +181,7 @@ mod tests {
         }
 
         assert_eq!(filter.fuzzy, Some("test-description".to_string()));
+        assert_eq!(filter.snoozed, Some(true));
     }
 
     #[test]
@@ -194,6 +201,7 @@ mod tests {
         assert!(filter.update_time.is_none());
         assert!(filter.completion_time.is_none());
         assert_eq!(filter.fuzzy, None);
+        assert_eq!(filter.snoozed, None);
     }
 
     #[test]
@@ -1,7 +1,7 @@
 use std::ffi::OsStr;
 
 use clap_complete::CompletionCandidate;
-use rutd_core::config::{Config, ConfigReflection};
+use rutd_core::config::{Config, ConfigManager, ConfigReflection};
 
 use super::utils::validate_utf8_or_empty;
 
@@ -19,6 +19,26 @@ pub fn complete_config_key(current: &OsStr) -> Vec<CompletionCandidate> {
         .collect()
 }
 
+/// Complete workspace names
+pub fn complete_workspace(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current_str) = validate_utf8_or_empty(current) else {
+        return vec![];
+    };
+    let Some(config) = ConfigManager::new()
+        .and_then(|manager| manager.get_effective_config())
+        .ok()
+    else {
+        return vec![];
+    };
+
+    config
+        .workspace_names()
+        .into_iter()
+        .filter(|name| name.starts_with(current_str))
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::ffi::OsString;
@@ -41,7 +61,7 @@ mod tests {
         let result = complete_config_key(&current);
 
         // Should return only git-related keys
-        assert_eq!(result.len(), 2);
+        assert_eq!(result.len(), 17);
     }
 
     #[test]
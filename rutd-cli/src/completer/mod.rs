@@ -4,9 +4,10 @@ pub mod sort_options;
 pub mod task_attribute;
 pub mod utils;
 
-pub use config::complete_config_key;
+pub use config::{complete_config_key, complete_workspace};
 pub use merge_strategy::complete_merge_strategy;
 pub use sort_options::complete_sort_options;
 pub use task_attribute::{
-    complete_id, complete_priority, complete_scope, complete_status, complete_type,
+    complete_id, complete_priority, complete_scope, complete_status, complete_trashed_id,
+    complete_type,
 };
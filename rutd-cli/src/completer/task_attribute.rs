@@ -21,6 +21,8 @@ impl CompletionContext {
     fn new() -> Option<Self> {
         let config_manager = ConfigManager::new().ok()?;
         let config = config_manager.get_effective_config().ok()?;
+        let workspace = config.workspace_name(None).to_string();
+        let config = config.with_workspace(&workspace).ok()?;
         let tasks = get_tasks(&config.path.task_dir_path());
         Some(Self { config, tasks })
     }
@@ -62,6 +64,31 @@ pub fn complete_id(current: &OsStr) -> Vec<CompletionCandidate> {
         .collect()
 }
 
+/// Get a list of trashed task IDs as completion candidates
+pub fn complete_trashed_id(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = validate_utf8_or_empty(current) else {
+        return vec![];
+    };
+
+    let Some(context) = CompletionContext::new() else {
+        return vec![]; // Fallback to empty if context creation fails
+    };
+
+    storage::load_trashed_tasks(&context.config.path.task_dir_path())
+        .unwrap_or_default()
+        .into_iter()
+        // Keep only those that start with the current prefix
+        .filter(|task| task.id.starts_with(current))
+        // Convert to completion candidates
+        .map(|task| {
+            // Take the first line of the task description as help text
+            let short_description = task.description.lines().next().map(String::from);
+            let truncated_id = task.id.chars().take(8).collect::<String>();
+            CompletionCandidate::new(truncated_id).help(short_description.map(StyledStr::from))
+        })
+        .collect()
+}
+
 /// Get a list of scopes as completion candidates
 pub fn complete_scope(current: &OsStr) -> Vec<CompletionCandidate> {
     let Some(current) = validate_utf8_or_empty(current) else {
@@ -176,10 +203,13 @@ mod tests {
             scope: scope.map(|s| s.to_string()),
             task_type: task_type.map(|t| t.to_string()),
             status,
-            created_at: "2023-01-01T12:00:00+00:00".to_string(),
+            created_at: chrono::DateTime::parse_from_rfc3339("2023-01-01T12:00:00+00:00").unwrap(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         }
     }
 
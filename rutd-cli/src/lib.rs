@@ -2,12 +2,19 @@ pub mod cli;
 pub mod completer;
 pub mod parser;
 
-use std::process::ExitCode;
+use std::{io::IsTerminal, process::ExitCode};
 
 use clap::{CommandFactory, Parser};
 use clap_complete::CompleteEnv;
-use cli::{Cli, Commands, DisplayManager, commands::ConfigCommands};
-use rutd_core::{Config, Display, SortOptions, TaskManager, config::ConfigManager};
+use cli::{
+    Cli, Commands, DisplayManager,
+    commands::{ConfigCommands, RemoteCommands, TrashCommands, WorkspaceCommands},
+};
+use rutd_core::{
+    Config, Display, Error, SortOptions, TaskManager,
+    config::{ConfigManager, TaskConfig},
+    task::{Filter, Modification},
+};
 
 pub fn app() -> ExitCode {
     // Check if we're being called for completion generation
@@ -18,11 +25,37 @@ pub fn app() -> ExitCode {
     let cli = Cli::parse();
 
     // Get configuration from environment variables
-    let Ok(config) = Config::new().inspect_err(|e| eprintln!("Failed to load configuration: {e}"))
+    let config_file = cli
+        .config_file
+        .clone()
+        .unwrap_or_else(Config::default_config_file);
+    let Ok(shared_config) = Config::from_file(&config_file)
+        .inspect_err(|e| eprintln!("Failed to load configuration: {e}"))
     else {
         return ExitCode::FAILURE;
     };
 
+    // Work in the selected workspace, workspaces and configuration can still
+    // be managed if it is missing
+    let workspace = shared_config
+        .workspace_name(cli.workspace.as_deref())
+        .to_string();
+    let config = match shared_config.clone().with_workspace(&workspace) {
+        Ok(config) => config,
+        Err(_)
+            if matches!(
+                cli.command,
+                Commands::Workspace { .. } | Commands::Config { .. }
+            ) =>
+        {
+            shared_config.clone()
+        }
+        Err(e) => {
+            eprintln!("Failed to load configuration: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
     // Initialize logging system
     if let Err(e) = rutd_core::logging::init_logger(
         cli.verbose,
@@ -39,12 +72,23 @@ pub fn app() -> ExitCode {
 
     let path_config = config.path;
     let git_config = config.git;
+    let task_config = config.task;
+    let task_dir = path_config.task_dir_path();
 
     // Create a display manager
     let display_manager = DisplayManager;
 
     // Build the task manager
-    let task_manager = TaskManager::new(path_config, git_config);
+    let dry_run = cli.dry_run;
+    let task_manager = TaskManager::new(path_config.clone(), git_config).with_dry_run(dry_run);
+
+    // Push the changes left behind by a failed automatic sync first, without
+    // failing the command. An explicit sync does it anyway.
+    let can_sync = matches!(cli.command, Commands::Sync { .. })
+        || report_auto_sync(
+            task_manager.retry_pending_sync(&display_manager),
+            &display_manager,
+        );
 
     // Handle different commands
     match cli.command {
@@ -57,8 +101,13 @@ pub fn app() -> ExitCode {
             log::trace!("Add task command");
             log::debug!("Add task: {description}");
             log::debug!("Priority: {priority}");
-            let scope = scope.inspect(|s| log::debug!("Task scope: {s}"));
-            let task_type = task_type.inspect(|t| log::debug!("Task type: {t}"));
+            // Fall back to the defaults of the configuration
+            let scope = scope
+                .or_else(|| Some(task_config.default_scope.clone()).filter(|s| !s.is_empty()))
+                .inspect(|s| log::debug!("Task scope: {s}"));
+            let task_type = task_type
+                .or_else(|| Some(task_config.default_type.clone()).filter(|t| !t.is_empty()))
+                .inspect(|t| log::debug!("Task type: {t}"));
 
             // Use TaskManager to add a new task
             if task_manager
@@ -74,22 +123,65 @@ pub fn app() -> ExitCode {
             filter,
             sort,
             stats,
+            all_workspaces: true,
+        } => {
+            log::trace!("List tasks of every workspace");
+            let Some(filter) = list_filter(filter.into(), &task_config, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
+            let sort = sort.unwrap_or_default();
+
+            let mut all_tasks = Vec::new();
+            for name in shared_config.workspace_names() {
+                let tasks = shared_config
+                    .clone()
+                    .with_workspace(&name)
+                    .and_then(|config| {
+                        let task_manager = TaskManager::new(config.path, config.git);
+                        let tasks = task_manager.list_tasks(&filter, Some(&sort));
+                        show_invalid_files(&task_manager, &display_manager);
+                        tasks
+                    });
+                let Ok(tasks) = tasks.inspect_err(|e| {
+                    display_manager
+                        .show_failure(&format!("Fail to load tasks of workspace '{name}': {e}"));
+                }) else {
+                    return ExitCode::FAILURE;
+                };
+                if !tasks.is_empty() {
+                    display_manager.show_workspace_tasks(&name, &tasks);
+                    all_tasks.extend(tasks);
+                }
+            }
+
+            if all_tasks.is_empty() {
+                display_manager.show_success("No tasks found");
+            } else if stats {
+                display_manager.show_task_stats(&all_tasks);
+            }
+        }
+        Commands::List {
+            filter,
+            sort,
+            stats,
+            all_workspaces: false,
         } => {
             log::trace!("List tasks");
             // Use the FilterOptions struct instead of individual parameters
+            let Some(filter) = list_filter(filter.into(), &task_config, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
 
             // Use TaskManager to list tasks
             let Ok(tasks) = task_manager
-                .list_tasks(
-                    &filter.into(),
-                    sort.or(Some(SortOptions::default())).as_ref(),
-                )
+                .list_tasks(&filter, sort.or(Some(SortOptions::default())).as_ref())
                 .inspect_err(|e| {
                     display_manager.show_failure(&format!("Fail to load tasks: {e}"));
                 })
             else {
                 return ExitCode::FAILURE;
             };
+            show_invalid_files(&task_manager, &display_manager);
 
             // Check if tasks are empty
             if tasks.is_empty() {
@@ -97,37 +189,88 @@ pub fn app() -> ExitCode {
                 return ExitCode::SUCCESS;
             }
 
+            // Renumber working tasks so the numbers shown can be used in
+            // following commands
+            let Ok(working_set) = task_manager.refresh_working_set().inspect_err(|e| {
+                display_manager.show_failure(&format!("Fail to number tasks: {e}"));
+            }) else {
+                return ExitCode::FAILURE;
+            };
+
             // Use DisplayManager to show tasks
-            display_manager.show_tasks_list(&tasks);
+            display_manager.show_numbered_tasks_list(&tasks, &working_set);
 
             if stats {
                 display_manager.show_task_stats(&tasks);
             }
         }
-        Commands::Done { id } => {
-            let id = id
-                .inspect(|id| {
-                    log::trace!("Mark task {id:?} as completed");
-                })
-                .or_else(|| {
-                    log::trace!("Mark active task as completed");
-                    None
-                });
+        Commands::Done { ids, filter, force } => {
+            let filter: Filter = filter.into();
 
-            // Use TaskManager to mark task as completed
-            if task_manager
-                .finish_task(id.as_deref())
-                .inspect(|id| display_manager.show_success(&format!("Task {id} marked as done")))
-                .inspect_err(|e| {
-                    display_manager.show_failure(&format!("Fail to mark task as done: {e}"))
-                })
-                .is_err()
-            {
-                return ExitCode::FAILURE;
+            if ids.len() > 1 || !filter.is_empty() {
+                log::trace!("Mark tasks {ids:?} as completed");
+                log::debug!("Filter: {filter:?}");
+                log::debug!("Force without confirmation: {force}");
+                let Some(ids) = resolve_working_task_ids(ids, &task_manager, &display_manager)
+                else {
+                    return ExitCode::FAILURE;
+                };
+
+                // Use TaskManager to mark all selected tasks as completed
+                if task_manager
+                    .finish_tasks(&ids, &filter, force, &display_manager)
+                    .inspect(|ids| {
+                        display_manager.show_success(&format!("Marked {} tasks as done", ids.len()))
+                    })
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to mark tasks as done: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
+            } else {
+                let id = ids
+                    .into_iter()
+                    .next()
+                    .inspect(|id| {
+                        log::trace!("Mark task {id:?} as completed");
+                    })
+                    .or_else(|| {
+                        log::trace!("Mark active task as completed");
+                        None
+                    });
+                let id = match id {
+                    Some(id) => {
+                        let Some(id) = resolve_working_task_id(id, &task_manager, &display_manager)
+                        else {
+                            return ExitCode::FAILURE;
+                        };
+                        Some(id)
+                    }
+                    None => None,
+                };
+
+                // Use TaskManager to mark task as completed
+                if task_manager
+                    .finish_task(id.as_deref())
+                    .inspect(|id| {
+                        display_manager.show_success(&format!("Task {id} marked as done"))
+                    })
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to mark task as done: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
             }
         }
         Commands::Edit { id } => {
             log::trace!("Edit task {id}");
+            let Some(id) = resolve_working_task_id(id, &task_manager, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
 
             // Use TaskManager to edit task description
             if task_manager
@@ -141,6 +284,9 @@ pub fn app() -> ExitCode {
         }
         Commands::Start { id } => {
             log::trace!("Start task {id}");
+            let Some(id) = resolve_working_task_id(id, &task_manager, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
 
             // Use TaskManager to start a task
             if task_manager
@@ -165,21 +311,185 @@ pub fn app() -> ExitCode {
                 return ExitCode::FAILURE;
             }
         }
-        Commands::Abort { id } => {
-            let id = id
-                .inspect(|id| {
-                    log::trace!("Abort task {id}");
+        Commands::Status {} => {
+            log::trace!("Show status");
+            match task_manager.active_task() {
+                Ok(active) => display_manager.show_active_task(active.as_ref()),
+                Err(e) => {
+                    display_manager.show_failure(&format!("Fail to get active task: {e}"));
+                    return ExitCode::FAILURE;
+                }
+            }
+            return show_sync_status(&task_manager, &display_manager);
+        }
+        Commands::Paths {} => {
+            log::trace!("Show paths");
+            display_manager.show_paths(&[
+                ("Config file", Some(config_file.clone())),
+                (
+                    "Project config",
+                    Config::current_project_config(&config_file),
+                ),
+                ("Tasks", Some(path_config.task_dir_path())),
+                ("Active task", Some(path_config.active_task_file_path())),
+                ("Working set", Some(path_config.working_set_file_path())),
+                ("Log file", Some(path_config.log_file_path())),
+            ]);
+        }
+        Commands::Abort { ids, filter, force } => {
+            let filter: Filter = filter.into();
+
+            if ids.len() > 1 || !filter.is_empty() {
+                log::trace!("Abort tasks {ids:?}");
+                log::debug!("Filter: {filter:?}");
+                log::debug!("Force without confirmation: {force}");
+                let Some(ids) = resolve_working_task_ids(ids, &task_manager, &display_manager)
+                else {
+                    return ExitCode::FAILURE;
+                };
+
+                // Use TaskManager to abort all selected tasks
+                if task_manager
+                    .abort_tasks(&ids, &filter, force, &display_manager)
+                    .inspect(|ids| {
+                        display_manager.show_success(&format!("Aborted {} tasks", ids.len()))
+                    })
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to abort tasks: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
+            } else {
+                let id = ids
+                    .into_iter()
+                    .next()
+                    .inspect(|id| {
+                        log::trace!("Abort task {id}");
+                    })
+                    .or_else(|| {
+                        log::trace!("Abort active task");
+                        None
+                    });
+                let id = match id {
+                    Some(id) => {
+                        let Some(id) = resolve_working_task_id(id, &task_manager, &display_manager)
+                        else {
+                            return ExitCode::FAILURE;
+                        };
+                        Some(id)
+                    }
+                    None => None,
+                };
+
+                // Use TaskManager to abort a task
+                if task_manager
+                    .abort_task(id.as_deref())
+                    .inspect(|id| display_manager.show_success(&format!("Aborted task {id}")))
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to abort task: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Commands::Modify {
+            ids,
+            filter,
+            set_priority,
+            set_scope,
+            set_type,
+            force,
+        } => {
+            log::trace!("Modify tasks {ids:?}");
+            let filter: Filter = filter.into();
+            log::debug!("Filter: {filter:?}");
+            log::debug!("Force without confirmation: {force}");
+            let modification = Modification {
+                priority: set_priority,
+                scope: set_scope,
+                task_type: set_type,
+            };
+            log::debug!("Modification: {modification:?}");
+            let Some(ids) = resolve_working_task_ids(ids, &task_manager, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
+
+            // Use TaskManager to modify all selected tasks
+            if task_manager
+                .modify_tasks(&ids, &filter, &modification, force, &display_manager)
+                .inspect(|ids| {
+                    display_manager.show_success(&format!("Modified {} tasks", ids.len()))
+                })
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to modify tasks: {e}"))
+                })
+                .is_err()
+            {
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Snooze {
+            ids,
+            filter,
+            until,
+            clear,
+            force,
+        } => {
+            log::trace!("Snooze tasks {ids:?}");
+            let filter: Filter = filter.into();
+            log::debug!("Filter: {filter:?}");
+            log::debug!("Force without confirmation: {force}");
+            let until = (!clear).then(|| until.fixed_offset());
+            log::debug!("Snooze until: {until:?}");
+            let Some(ids) = resolve_working_task_ids(ids, &task_manager, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
+
+            // Use TaskManager to snooze all selected tasks
+            if task_manager
+                .snooze_tasks(&ids, &filter, until, force, &display_manager)
+                .inspect(|ids| {
+                    let message = match until {
+                        Some(until) => format!("Snoozed {} tasks until {until}", ids.len()),
+                        None => format!("Unsnoozed {} tasks", ids.len()),
+                    };
+                    display_manager.show_success(&message)
                 })
-                .or_else(|| {
-                    log::trace!("Abort active task");
-                    None
-                });
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to snooze tasks: {e}"))
+                })
+                .is_err()
+            {
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Annotate {
+            ids,
+            filter,
+            text,
+            force,
+        } => {
+            log::trace!("Annotate tasks {ids:?}");
+            let filter: Filter = filter.into();
+            log::debug!("Filter: {filter:?}");
+            log::debug!("Force without confirmation: {force}");
+            let Some(ids) = resolve_working_task_ids(ids, &task_manager, &display_manager) else {
+                return ExitCode::FAILURE;
+            };
 
-            // Use TaskManager to abort a task
+            // Use TaskManager to annotate all selected tasks
             if task_manager
-                .abort_task(id.as_deref())
-                .inspect(|id| display_manager.show_success(&format!("Aborted task {id}")))
-                .inspect_err(|e| display_manager.show_failure(&format!("Fail to abort task: {e}")))
+                .annotate_tasks(&ids, &filter, &text, force, &display_manager)
+                .inspect(|ids| {
+                    display_manager.show_success(&format!("Annotated {} tasks", ids.len()))
+                })
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to annotate tasks: {e}"))
+                })
                 .is_err()
             {
                 return ExitCode::FAILURE;
@@ -200,22 +510,222 @@ pub fn app() -> ExitCode {
                 return ExitCode::FAILURE;
             }
         }
-        Commands::Sync { prefer } => {
-            log::trace!("Sync with remote repository");
-            log::debug!("Conflict resolution preference: {prefer}");
+        Commands::Restore { id } => {
+            log::trace!("Restore task {id}");
+            let Some(id) = resolve_task_id(
+                &id,
+                |id| task_manager.resolve_deleted_task_id(id),
+                &task_manager,
+                &display_manager,
+            ) else {
+                return ExitCode::FAILURE;
+            };
 
-            // Use TaskManager to sync with remote repository
+            // Use TaskManager to restore a deleted task
             if task_manager
-                .sync(prefer)
-                .inspect(|_| {
-                    display_manager.show_success("Successfully synced with remote repository")
+                .restore_task(&id)
+                .inspect(|id| display_manager.show_success(&format!("Restored task {id}")))
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to restore task: {e}"))
+                })
+                .is_err()
+            {
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Trash { command } => match command {
+            TrashCommands::List { sort } => {
+                log::trace!("List tasks in trash");
+
+                let Ok(tasks) = task_manager
+                    .list_trash(sort.or(Some(SortOptions::default())).as_ref())
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to load trash: {e}"));
+                    })
+                else {
+                    return ExitCode::FAILURE;
+                };
+
+                if tasks.is_empty() {
+                    display_manager.show_success("Trash is empty");
+                    return ExitCode::SUCCESS;
+                }
+
+                display_manager.show_tasks_list(&tasks);
+            }
+            TrashCommands::Empty { older_than, force } => {
+                log::trace!("Empty trash");
+                log::debug!("Only tasks deleted before: {older_than:?}");
+                log::debug!("Force empty without confirmation: {force}");
+
+                if task_manager
+                    .empty_trash(older_than, force, &display_manager)
+                    .inspect(|count| {
+                        display_manager.show_success(&format!("Permanently deleted {count} tasks"))
+                    })
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to empty trash: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
+            }
+        },
+        Commands::Log { id } => {
+            log::trace!("Show history of task {id}");
+            let Some(id) = resolve_task_id(
+                &id,
+                |id| task_manager.resolve_any_task_id(id),
+                &task_manager,
+                &display_manager,
+            ) else {
+                return ExitCode::FAILURE;
+            };
+
+            let Ok(entries) = task_manager.task_log(&id).inspect_err(|e| {
+                display_manager.show_failure(&format!("Fail to get task history: {e}"));
+            }) else {
+                return ExitCode::FAILURE;
+            };
+            display_manager.show_task_log(&entries);
+        }
+        Commands::Activity { since } => {
+            log::trace!("Show activity");
+            log::debug!("Date range: {since:?}");
+
+            let Ok(groups) = task_manager.activity(&since).inspect_err(|e| {
+                display_manager.show_failure(&format!("Fail to get activity: {e}"));
+            }) else {
+                return ExitCode::FAILURE;
+            };
+            display_manager.show_activity(&groups);
+        }
+        Commands::Undo { count, force } => {
+            log::trace!("Undo last {count} changes");
+            log::debug!("Force undo without confirmation: {force}");
+
+            let Ok(plan) = task_manager.plan_undo(count).inspect_err(|e| {
+                display_manager.show_failure(&format!("Fail to undo changes: {e}"));
+            }) else {
+                return ExitCode::FAILURE;
+            };
+
+            // Preview the changes before undoing them
+            display_manager.show_undo_preview(&plan);
+            if plan.is_pushed() {
+                display_manager.show_warning(
+                    "Some of these changes have already been pushed, they will be reverted with a new commit",
+                );
+            }
+            if !force
+                && !display_manager
+                    .confirm(&format!("Undo {count} changes?"))
+                    .unwrap_or(false)
+            {
+                return ExitCode::SUCCESS;
+            }
+
+            if task_manager
+                .undo(&plan)
+                .inspect(|_| display_manager.show_success(&format!("Undid {count} changes")))
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to undo changes: {e}"))
+                })
+                .is_err()
+            {
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Migrate {} => {
+            log::trace!("Migrate tasks");
+
+            if task_manager
+                .migrate_tasks()
+                .inspect(|count| match count {
+                    0 => display_manager.show_success("No tasks to migrate"),
+                    count => display_manager.show_success(&format!(
+                        "Migrated {count} tasks to the current schema version"
+                    )),
+                })
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to migrate tasks: {e:#}"))
+                })
+                .is_err()
+            {
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Commit { message } => {
+            log::trace!("Commit pending changes");
+            let message = message.inspect(|m| log::debug!("Commit message: {m}"));
+
+            if task_manager
+                .commit_pending_changes(message.as_deref())
+                .inspect(|ids| match ids.len() {
+                    0 => display_manager.show_success("No pending changes to commit"),
+                    count => {
+                        display_manager.show_success(&format!("Committed changes to {count} tasks"))
+                    }
+                })
+                .inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to commit changes: {e}"))
                 })
-                .inspect_err(|e| display_manager.show_failure(&format!("Fail to sync tasks: {e}")))
                 .is_err()
             {
                 return ExitCode::FAILURE;
             }
         }
+        Commands::Sync {
+            prefer,
+            r#continue,
+            abort,
+            conflicts,
+            status,
+        } => {
+            if status {
+                log::trace!("Show sync status");
+                return show_sync_status(&task_manager, &display_manager);
+            }
+            if conflicts {
+                log::trace!("List sync conflicts");
+                let Ok(conflicts) = task_manager.sync_conflicts().inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to list conflicts: {e}"));
+                }) else {
+                    return ExitCode::FAILURE;
+                };
+                display_manager.show_sync_conflicts(&conflicts, &task_dir);
+                return ExitCode::SUCCESS;
+            }
+
+            let result = if r#continue {
+                log::trace!("Continue sync with remote repository");
+                task_manager.continue_sync(prefer, &display_manager)
+            } else if abort {
+                log::trace!("Abort sync with remote repository");
+                task_manager.abort_sync().inspect(|_| {
+                    display_manager.show_success("Aborted sync with remote repository")
+                })
+            } else {
+                log::trace!("Sync with remote repository");
+                log::debug!("Conflict resolution preference: {prefer}");
+                task_manager.sync(prefer, &display_manager)
+            };
+
+            if let Err(e) = result {
+                display_manager.show_failure(&format!("Fail to sync tasks: {e}"));
+                // Show what is left to resolve
+                if matches!(e.root(), Error::GitConflict { .. })
+                    && let Ok(conflicts) = task_manager.sync_conflicts()
+                {
+                    display_manager.show_sync_conflicts(&conflicts, &task_dir);
+                }
+                return ExitCode::FAILURE;
+            }
+            if !abort {
+                display_manager.show_success("Successfully synced with remote repository");
+            }
+        }
         Commands::Clone { url } => {
             log::trace!("Clone remote repository");
             log::debug!("Repository URL: {url}");
@@ -235,15 +745,121 @@ pub fn app() -> ExitCode {
                 return ExitCode::FAILURE;
             }
         }
+        Commands::Remote { command } => match command {
+            RemoteCommands::Add { url, name } => {
+                log::trace!("Add remote repository: {url}");
+                let name = name.inspect(|n| log::debug!("Remote name: {n}"));
+
+                if task_manager
+                    .add_remote(name.as_deref(), &url)
+                    .inspect(|name| {
+                        display_manager.show_success(&format!("Added remote '{name}': {url}"))
+                    })
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to add remote: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
+            }
+            RemoteCommands::SetUrl { url, name } => {
+                log::trace!("Set remote repository URL: {url}");
+                let name = name.inspect(|n| log::debug!("Remote name: {n}"));
+
+                if task_manager
+                    .set_remote_url(name.as_deref(), &url)
+                    .inspect(|name| {
+                        display_manager.show_success(&format!("Set URL of remote '{name}': {url}"))
+                    })
+                    .inspect_err(|e| {
+                        display_manager.show_failure(&format!("Fail to set remote URL: {e}"))
+                    })
+                    .is_err()
+                {
+                    return ExitCode::FAILURE;
+                }
+            }
+            RemoteCommands::Show => {
+                log::trace!("Show remote repositories");
+
+                let Ok(remotes) = task_manager.remotes().inspect_err(|e| {
+                    display_manager.show_failure(&format!("Fail to list remotes: {e}"));
+                }) else {
+                    return ExitCode::FAILURE;
+                };
+                // A detached HEAD is reported when syncing
+                let target = task_manager
+                    .sync_target()
+                    .inspect_err(|e| log::debug!("No sync target: {e}"))
+                    .ok();
+                display_manager.show_remotes(&remotes, target.as_ref());
+            }
+        },
+        Commands::Workspace { command } => {
+            let config_manager = ConfigManager::with_path(&config_file);
+
+            match command {
+                WorkspaceCommands::List => {
+                    log::trace!("List workspaces");
+                    let workspaces = shared_config
+                        .workspace_names()
+                        .into_iter()
+                        .filter_map(|name| {
+                            let config = shared_config.clone().with_workspace(&name).ok()?;
+                            Some((name, config.path.root_path()))
+                        })
+                        .collect::<Vec<_>>();
+                    display_manager.show_workspaces(&workspaces, &workspace);
+                }
+                WorkspaceCommands::Add { name, root_dir } => {
+                    log::trace!("Add workspace {name}: {root_dir}");
+
+                    if dry_run {
+                        display_manager.show_warning(&format!(
+                            "Dry run, would add workspace '{name}' in {root_dir}"
+                        ));
+                    } else if config_manager
+                        .add_workspace(&name, &root_dir)
+                        .inspect(|_| {
+                            display_manager
+                                .show_success(&format!("Added workspace '{name}' in {root_dir}"))
+                        })
+                        .inspect_err(|e| {
+                            display_manager.show_failure(&format!("Failed to add workspace: {e}"))
+                        })
+                        .is_err()
+                    {
+                        return ExitCode::FAILURE;
+                    }
+                }
+                WorkspaceCommands::Use { name } => {
+                    log::trace!("Switch to workspace {name}");
+
+                    if dry_run {
+                        display_manager
+                            .show_warning(&format!("Dry run, would switch to workspace '{name}'"));
+                    } else if config_manager
+                        .use_workspace(&name)
+                        .inspect(|_| {
+                            display_manager.show_success(&format!("Switched to workspace '{name}'"))
+                        })
+                        .inspect_err(|e| {
+                            display_manager
+                                .show_failure(&format!("Failed to switch workspace: {e}"))
+                        })
+                        .is_err()
+                    {
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+        }
         Commands::Config { command } => {
             log::trace!("Config command");
 
             // Create a config manager
-            let Ok(config_manager) = ConfigManager::new().inspect_err(|e| {
-                display_manager.show_failure(&format!("Failed to create config manager: {e}"))
-            }) else {
-                return ExitCode::FAILURE;
-            };
+            let config_manager = ConfigManager::with_path(&config_file);
 
             match command {
                 ConfigCommands::Get { key } => {
@@ -264,7 +880,10 @@ pub fn app() -> ExitCode {
                 ConfigCommands::Set { key, value } => {
                     log::trace!("Set config value for key: {key}");
 
-                    if config_manager
+                    if dry_run {
+                        display_manager
+                            .show_warning(&format!("Dry run, would set {key} = {value}"));
+                    } else if config_manager
                         .set_config_value(&key, &value)
                         .inspect(|_| display_manager.show_success(&format!("Set {key} = {value}")))
                         .inspect_err(|e| {
@@ -300,7 +919,11 @@ pub fn app() -> ExitCode {
                 ConfigCommands::Unset { key } => {
                     log::trace!("Unset config value: {key}");
 
-                    if config_manager
+                    if dry_run {
+                        display_manager.show_warning(&format!(
+                            "Dry run, would remove configuration key '{key}'"
+                        ));
+                    } else if config_manager
                         .unset_config_value(&key)
                         .inspect(|_| {
                             display_manager
@@ -319,6 +942,164 @@ pub fn app() -> ExitCode {
         }
     }
 
+    // Tell about tasks left out by the command
+    show_invalid_files(&task_manager, &display_manager);
+
+    // Show what would have been written
+    if dry_run {
+        display_manager.show_dry_run(&task_manager.take_pending_changes());
+    }
+
+    // Push the changes made by the command if asked to, without failing the
+    // command. Commands only reading tasks never reach the remote repository,
+    // and a sync that just failed is not retried right away.
+    if !task_manager.has_changes() || !can_sync {
+        return ExitCode::SUCCESS;
+    }
+    report_auto_sync(task_manager.auto_sync(&display_manager), &display_manager);
+
     // Catch-all for normal exit
     ExitCode::SUCCESS
 }
+
+/// Resolve the ID of a task that is not deleted, see [`resolve_task_id`]
+fn resolve_working_task_id(
+    id: String,
+    task_manager: &TaskManager,
+    display_manager: &DisplayManager,
+) -> Option<String> {
+    resolve_task_id(
+        &id,
+        |id| task_manager.resolve_task_id(id),
+        task_manager,
+        display_manager,
+    )
+}
+
+/// Resolve the IDs of several tasks that are not deleted
+///
+/// Returns `None` if any of them cannot be resolved.
+fn resolve_working_task_ids(
+    ids: Vec<String>,
+    task_manager: &TaskManager,
+    display_manager: &DisplayManager,
+) -> Option<Vec<String>> {
+    ids.into_iter()
+        .map(|id| resolve_working_task_id(id, task_manager, display_manager))
+        .collect()
+}
+
+/// Resolve a task ID with a resolver of the task manager, letting the user
+/// choose a task when the ID is ambiguous
+///
+/// Failures are reported and `None` is returned, as well as when the terminal
+/// is not interactive or the user cancels the selection.
+fn resolve_task_id(
+    id: &str,
+    resolve: impl Fn(&str) -> rutd_core::Result<String>,
+    task_manager: &TaskManager,
+    display_manager: &DisplayManager,
+) -> Option<String> {
+    let e = match resolve(id) {
+        Ok(id) => return Some(id),
+        Err(e) => e,
+    };
+    let Error::AmbiguousId { id, candidates } = e.root() else {
+        display_manager.show_failure(&format!("Fail to find task: {e}"));
+        return None;
+    };
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        display_manager.show_failure(&format!("Fail to find task: {e}"));
+        return None;
+    }
+
+    log::debug!("Task ID {id} matches {} tasks", candidates.len());
+    let selection = task_manager
+        .candidate_tasks(candidates)
+        .map_err(anyhow::Error::from)
+        .and_then(|tasks| {
+            let index = display_manager.select_task(id, &tasks)?;
+            Ok(index.map(|index| tasks[index].id.clone()))
+        });
+    match selection {
+        Ok(Some(id)) => Some(id),
+        Ok(None) => {
+            display_manager.show_failure("No task selected");
+            None
+        }
+        Err(e) => {
+            display_manager.show_failure(&format!("Fail to select task: {e}"));
+            None
+        }
+    }
+}
+
+/// Warn about a failed automatic sync
+///
+/// Returns whether the sync did not fail.
+fn report_auto_sync(result: rutd_core::Result<bool>, display_manager: &DisplayManager) -> bool {
+    match &result {
+        Ok(true) => log::info!("Synced automatically with remote repository"),
+        Ok(false) => {}
+        Err(e) if e.is_offline() => display_manager.show_warning(&format!(
+            "Remote repository unreachable, will sync next time: {e}"
+        )),
+        Err(e) => display_manager.show_warning(&format!(
+            "Fail to sync automatically, will retry next time: {e}"
+        )),
+    }
+    result.is_ok()
+}
+
+/// Warn about task files skipped because they could not be loaded
+fn show_invalid_files(task_manager: &TaskManager, display_manager: &DisplayManager) {
+    for file in task_manager.take_invalid_files() {
+        display_manager.show_warning(&format!(
+            "Skipped invalid task file {}: {}",
+            file.path.display(),
+            file.error
+        ));
+    }
+}
+
+/// Show whether local changes are pushed, as of the last fetch
+fn show_sync_status(task_manager: &TaskManager, display_manager: &DisplayManager) -> ExitCode {
+    let result = task_manager.sync_status().and_then(|status| {
+        let unpushed = match status {
+            Some(_) => task_manager.unpushed_changes()?,
+            None => Vec::new(),
+        };
+        Ok((status, unpushed))
+    });
+    match result {
+        Ok((status, unpushed)) => {
+            display_manager.show_sync_status(status.as_ref(), &unpushed);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            display_manager.show_failure(&format!("Fail to get sync status: {e}"));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Complete the filter given on the command line with the default list filter
+/// of the configuration
+fn list_filter(
+    filter: Filter,
+    task_config: &TaskConfig,
+    display_manager: &DisplayManager,
+) -> Option<Filter> {
+    task_config
+        .list_filter()
+        .map(|defaults| {
+            // Snoozed tasks are hidden unless asked for
+            let filter = filter.or(defaults);
+            Filter {
+                snoozed: filter.snoozed.or(Some(false)),
+                ..filter
+            }
+        })
+        .inspect_err(|e| display_manager.show_failure(&format!("Fail to load list filter: {e}")))
+        .ok()
+}
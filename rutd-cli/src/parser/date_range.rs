@@ -31,6 +31,32 @@ pub fn parse_date_range(range_str: &str) -> Result<DateRange, anyhow::Error> {
     }
 }
 
+// Parse a single date from string for clap, rounded to the start of its cycle
+pub fn parse_date_cutoff(date_str: &str) -> Result<DateTime<Local>, anyhow::Error> {
+    parse_date(date_str, Local::now(), false)
+}
+
+// Parse a date from string for clap, counting relative dates forward, e.g.
+// "3d" for the start of the day in three days
+pub fn parse_date_ahead(date_str: &str) -> Result<DateTime<Local>, anyhow::Error> {
+    let date_str = date_str.trim();
+    let now = Local::now();
+    if !date_str.ends_with(|c| "dwmy".contains(c)) {
+        return parse_absolute_date(date_str, now, false);
+    }
+
+    let (offset_days, offset_months, _) = parse_offset(date_str)?;
+    let date = now
+        .date_naive()
+        .checked_add_months(Months::new(offset_months))
+        .and_then(|date| date.checked_add_days(Days::new(offset_days.into())))
+        .context(format!("Failed to add offset to date: {date_str}"))?;
+    unwrap_ambiguous_date(
+        Local.with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0),
+        false,
+    )
+}
+
 /// Try parsing the date string from the current date
 fn parse_date<Tz: TimeZone>(
     date_str: &str,
@@ -134,46 +160,7 @@ fn parse_relative_date<Tz: TimeZone>(
     // Check if exact mode (starts with '+')
     let exact = date_str.starts_with('+');
     let date_str = if exact { &date_str[1..] } else { date_str };
-
-    // Parse multiple units
-    let mut remaining = date_str;
-    let mut offset_days = 0;
-    let mut offset_months = 0;
-    let mut last_unit = 'd'; // Default unit
-
-    // Regex would be cleaner but avoiding additional dependencies
-    while !remaining.is_empty() {
-        // Find the next unit (d, w, m, y)
-        let Some(pos) = remaining.find(|c| "dwmy".contains(c)) else {
-            anyhow::bail!("Missing unit (d/w/m/y): {}", remaining);
-        };
-
-        let unit = remaining.chars().nth(pos).unwrap();
-        let num_str = &remaining[..pos];
-
-        // Parse the number (empty means 0, like in 'd', 'w')
-        let num = if num_str.is_empty() {
-            0
-        } else {
-            match num_str.parse::<u32>() {
-                Ok(num) => num,
-                _ => anyhow::bail!("Invalid number in date component: {}", num_str),
-            }
-        };
-
-        // Accumulate the offset
-        match unit {
-            'd' => offset_days += num,
-            'w' => offset_days += num * 7,
-            'm' => offset_months += num,
-            'y' => offset_months += num * 12,
-            _ => unreachable!(),
-        }
-
-        // Update last unit and remaining string
-        last_unit = unit;
-        remaining = &remaining[pos + 1..];
-    }
+    let (offset_days, offset_months, last_unit) = parse_offset(date_str)?;
 
     // Calculate the date by subtracting the accumulated offset
     let mut datetime = now
@@ -235,6 +222,51 @@ fn parse_relative_date<Tz: TimeZone>(
     Ok(datetime)
 }
 
+/// Parse the offset of a relative date, e.g. "1m2d", as a number of days and
+/// months, along with the last unit
+fn parse_offset(date_str: &str) -> Result<(u32, u32, char)> {
+    let mut remaining = date_str;
+    let mut offset_days = 0;
+    let mut offset_months = 0;
+    let mut last_unit = 'd'; // Default unit
+
+    // Regex would be cleaner but avoiding additional dependencies
+    while !remaining.is_empty() {
+        // Find the next unit (d, w, m, y)
+        let Some(pos) = remaining.find(|c| "dwmy".contains(c)) else {
+            anyhow::bail!("Missing unit (d/w/m/y): {}", remaining);
+        };
+
+        let unit = remaining.chars().nth(pos).unwrap();
+        let num_str = &remaining[..pos];
+
+        // Parse the number (empty means 0, like in 'd', 'w')
+        let num = if num_str.is_empty() {
+            0
+        } else {
+            match num_str.parse::<u32>() {
+                Ok(num) => num,
+                _ => anyhow::bail!("Invalid number in date component: {}", num_str),
+            }
+        };
+
+        // Accumulate the offset
+        match unit {
+            'd' => offset_days += num,
+            'w' => offset_days += num * 7,
+            'm' => offset_months += num,
+            'y' => offset_months += num * 12,
+            _ => unreachable!(),
+        }
+
+        // Update last unit and remaining string
+        last_unit = unit;
+        remaining = &remaining[pos + 1..];
+    }
+
+    Ok((offset_days, offset_months, last_unit))
+}
+
 fn unwrap_ambiguous_date<Tz: TimeZone>(
     date: LocalResult<DateTime<Tz>>,
     is_end: bool,
@@ -431,4 +463,34 @@ mod tests {
         let result = parse_date_range("2023/01/32"); // Invalid day
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_date_cutoff() {
+        // Relative dates are rounded to the start of the cycle
+        let cutoff = parse_date_cutoff("30d").unwrap();
+        assert_eq!(cutoff.time().hour(), 0);
+        assert!(cutoff < Local::now());
+
+        // Absolute dates are supported as well
+        let cutoff = parse_date_cutoff("2023/01/01").unwrap();
+        assert_eq!(cutoff, create_date(2023, 1, 1));
+
+        // Ranges are not a single date
+        assert!(parse_date_cutoff("2023-2024").is_err());
+    }
+
+    #[test]
+    fn test_date_ahead() {
+        // Relative dates are counted forward, from the start of the day
+        let date = parse_date_ahead("1w2d").unwrap();
+        let expected = Local::now().date_naive() + Days::new(9);
+        assert_eq!(date.date_naive(), expected);
+        assert_eq!(date.time().hour(), 0);
+
+        // Absolute dates are supported as well
+        let date = parse_date_ahead("2030/01/01").unwrap();
+        assert_eq!(date, create_date(2030, 1, 1));
+
+        assert!(parse_date_ahead("soon").is_err());
+    }
 }
@@ -1,5 +1,5 @@
 pub mod date_range;
 pub mod sort_options;
 
-pub use date_range::parse_date_range;
+pub use date_range::{parse_date_ahead, parse_date_cutoff, parse_date_range};
 pub use sort_options::parse_sort_options;
@@ -0,0 +1,134 @@
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// Name of the configuration file in the configuration directory
+pub const CONFIG_FILE: &str = "config.toml";
+/// Directory holding everything before XDG base directories were supported
+pub const LEGACY_DIR: &str = "~/.rutd";
+
+/// Name of the application, used for its directories and environment
+/// variables
+///
+/// Resolved from the package name, so this is `rutd` for all binaries (e.g.
+/// `rutd`, `rutd-cli`, `rutd-tui`, etc.)
+pub fn app_name() -> &'static str {
+    env!("CARGO_PKG_NAME")
+        .split_once('-')
+        .map_or(env!("CARGO_PKG_NAME"), |(name, _)| name)
+}
+
+/// Base directories of the configuration, the task repository and the state
+/// (active task, working set and log)
+///
+/// Follows the XDG base directory specification, unless a legacy `~/.rutd`
+/// directory exists and XDG directories don't, in which case everything is
+/// kept in `~/.rutd` as before.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BaseDirs {
+    /// Directory of the configuration file
+    pub config_dir: PathBuf,
+    /// Directory of the task repository
+    pub data_dir: PathBuf,
+    /// Directory of the active task, working set and log
+    pub state_dir: PathBuf,
+}
+
+impl BaseDirs {
+    /// Resolve the base directories from the environment
+    pub fn new() -> Self {
+        let home = PathBuf::from(shellexpand::tilde("~").as_ref());
+        let legacy_dir = PathBuf::from(shellexpand::tilde(LEGACY_DIR).as_ref());
+        Self::resolve(&home, &legacy_dir, |name| env::var_os(name))
+    }
+
+    /// Resolve the base directories of a home directory, reading XDG
+    /// variables with `var`
+    fn resolve(home: &Path, legacy_dir: &Path, var: impl Fn(&str) -> Option<OsString>) -> Self {
+        // Relative paths are invalid according to the specification, and
+        // must be ignored
+        let xdg_dir = |name: &str, default: &str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .unwrap_or_else(|| home.join(default))
+                .join(app_name())
+        };
+        let dirs = Self {
+            config_dir: xdg_dir("XDG_CONFIG_HOME", ".config"),
+            data_dir: xdg_dir("XDG_DATA_HOME", ".local/share"),
+            state_dir: xdg_dir("XDG_STATE_HOME", ".local/state"),
+        };
+
+        // Keep using the legacy directory until moving to XDG directories
+        if legacy_dir.is_dir() && !dirs.config_dir.exists() && !dirs.data_dir.exists() {
+            return Self::legacy(legacy_dir);
+        }
+        dirs
+    }
+
+    /// Keep everything in a single directory
+    fn legacy(dir: &Path) -> Self {
+        Self {
+            config_dir: dir.to_path_buf(),
+            data_dir: dir.to_path_buf(),
+            state_dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Path of the configuration file
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join(CONFIG_FILE)
+    }
+}
+
+impl Default for BaseDirs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_base_dirs() {
+        let temp_dir = tempdir().unwrap();
+        let home = temp_dir.path();
+        let legacy_dir = home.join(".rutd");
+
+        // XDG defaults
+        let dirs = BaseDirs::resolve(home, &legacy_dir, |_| None);
+        assert_eq!(dirs.config_dir, home.join(".config/rutd"));
+        assert_eq!(dirs.data_dir, home.join(".local/share/rutd"));
+        assert_eq!(dirs.state_dir, home.join(".local/state/rutd"));
+        assert_eq!(dirs.config_file(), home.join(".config/rutd/config.toml"));
+
+        // XDG variables, ignoring relative paths
+        let dirs = BaseDirs::resolve(home, &legacy_dir, |name| match name {
+            "XDG_CONFIG_HOME" => Some("/xdg/config".into()),
+            "XDG_DATA_HOME" => Some("relative/data".into()),
+            _ => None,
+        });
+        assert_eq!(dirs.config_dir, PathBuf::from("/xdg/config/rutd"));
+        assert_eq!(dirs.data_dir, home.join(".local/share/rutd"));
+
+        // An existing legacy directory is still used
+        fs::create_dir_all(&legacy_dir).unwrap();
+        let dirs = BaseDirs::resolve(home, &legacy_dir, |_| None);
+        assert_eq!(dirs, BaseDirs::legacy(&legacy_dir));
+        assert_eq!(dirs.config_file(), legacy_dir.join("config.toml"));
+
+        // Until XDG directories are used
+        fs::create_dir_all(home.join(".config/rutd")).unwrap();
+        let dirs = BaseDirs::resolve(home, &legacy_dir, |_| None);
+        assert_eq!(dirs.config_dir, home.join(".config/rutd"));
+    }
+}
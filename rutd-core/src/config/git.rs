@@ -1,12 +1,142 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
-/// Git configuration for authentication
+/// Minutes between automatic syncs in `interval` mode when not configured
+const DEFAULT_AUTO_SYNC_INTERVAL: u64 = 15;
+/// Seconds to wait for the remote during automatic syncs when not configured
+const DEFAULT_AUTO_SYNC_TIMEOUT: u64 = 10;
+
+/// When changes to tasks are committed
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoCommit {
+    /// Commit every change right away
+    #[default]
+    Always,
+    /// Leave changes uncommitted until the next sync
+    OnSync,
+    /// Leave changes uncommitted until they are committed explicitly
+    Never,
+}
+
+/// How local commits are combined with remote ones when syncing
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// Join both histories with a merge commit
+    #[default]
+    Merge,
+    /// Replay local commits on top of the remote ones
+    Rebase,
+}
+
+/// When tasks are synced with the remote repository without being asked to
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoSync {
+    /// Only sync when asked to
+    #[default]
+    Off,
+    /// Sync after every command leaving changes to push
+    OnWrite,
+    /// Sync after commands leaving changes to push, at most once per interval
+    Interval,
+}
+
+/// How commits are signed
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitSigning {
+    /// Commits are not signed
+    #[default]
+    None,
+    /// Sign commits with GnuPG
+    Gpg,
+    /// Sign commits with an SSH key
+    Ssh,
+}
+
+/// Git configuration for authentication and committing
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct GitConfig {
     /// Git username for authentication
     pub username: String,
-    /// Git password for authentication
+    /// Git password or token for authentication
+    ///
+    /// Prefer `password_env`, `password_command` or a git credential helper
+    /// to storing it in the configuration file.
     pub password: String,
+    /// Environment variable holding the password or token
+    pub password_env: String,
+    /// Shell command printing the password or token
+    pub password_command: String,
+    /// Path to the SSH private key
+    ///
+    /// Falls back to the usual keys in `~/.ssh`, then the SSH agent, if empty.
+    pub ssh_key: String,
+    /// Environment variable holding the passphrase of the SSH key
+    pub ssh_passphrase_env: String,
+    /// Remote to sync with
+    ///
+    /// Falls back to the upstream of the current branch, then `origin`, if
+    /// empty.
+    pub remote: String,
+    /// Branch on the remote to sync with
+    ///
+    /// Falls back to the upstream of the current branch, then the branch of
+    /// the same name, if empty.
+    pub branch: String,
+    /// How local commits are combined with remote ones when syncing
+    pub sync_mode: SyncMode,
+    /// When tasks are synced with the remote repository without being asked
+    /// to
+    pub auto_sync: AutoSync,
+    /// Minutes between automatic syncs in `interval` mode
+    ///
+    /// Falls back to 15 minutes if 0.
+    pub auto_sync_interval: u64,
+    /// Seconds to wait for the remote repository during automatic syncs
+    ///
+    /// Falls back to 10 seconds if 0.
+    pub auto_sync_timeout: u64,
+    /// When changes to tasks are committed
+    pub auto_commit: AutoCommit,
+    /// Name of the author of commits
+    ///
+    /// Falls back to `user.name` from the git configuration if empty.
+    pub author_name: String,
+    /// Email of the author of commits
+    ///
+    /// Falls back to `user.email` from the git configuration if empty.
+    pub author_email: String,
+    /// How commits are signed
+    pub signing: CommitSigning,
+    /// Key used to sign commits
+    ///
+    /// A GnuPG key ID or the path to an SSH key. Falls back to
+    /// `user.signingkey` from the git configuration if empty.
+    pub signing_key: String,
+}
+
+impl GitConfig {
+    /// Time between automatic syncs in `interval` mode
+    pub fn sync_interval(&self) -> Duration {
+        let minutes = match self.auto_sync_interval {
+            0 => DEFAULT_AUTO_SYNC_INTERVAL,
+            minutes => minutes,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    /// Time to wait for the remote repository during automatic syncs
+    pub fn sync_timeout(&self) -> Duration {
+        let seconds = match self.auto_sync_timeout {
+            0 => DEFAULT_AUTO_SYNC_TIMEOUT,
+            seconds => seconds,
+        };
+        Duration::from_secs(seconds)
+    }
 }
 
 #[cfg(test)]
@@ -20,6 +150,21 @@ mod tests {
         // Default values should be empty strings
         assert_eq!(config.username, "");
         assert_eq!(config.password, "");
+        assert_eq!(config.password_env, "");
+        assert_eq!(config.password_command, "");
+        assert_eq!(config.ssh_key, "");
+        assert_eq!(config.ssh_passphrase_env, "");
+        assert_eq!(config.remote, "");
+        assert_eq!(config.branch, "");
+        assert_eq!(config.sync_mode, SyncMode::Merge);
+        assert_eq!(config.auto_sync, AutoSync::Off);
+        assert_eq!(config.auto_sync_interval, 0);
+        assert_eq!(config.auto_sync_timeout, 0);
+        assert_eq!(config.auto_commit, AutoCommit::Always);
+        assert_eq!(config.author_name, "");
+        assert_eq!(config.author_email, "");
+        assert_eq!(config.signing, CommitSigning::None);
+        assert_eq!(config.signing_key, "");
     }
 
     #[test]
@@ -27,6 +172,7 @@ mod tests {
         let config = GitConfig {
             username: "test-user".to_string(),
             password: "test-password".to_string(),
+            ..Default::default()
         };
 
         // Check custom values were set correctly
@@ -39,6 +185,7 @@ mod tests {
         let config = GitConfig {
             username: "test-user".to_string(),
             password: "test-password".to_string(),
+            ..Default::default()
         };
 
         // Serialize to TOML
@@ -57,4 +204,59 @@ mod tests {
         assert_eq!(deserialized.username, config.username);
         assert_eq!(deserialized.password, config.password);
     }
+
+    #[test]
+    fn test_auto_commit_serialization() {
+        let config = GitConfig {
+            auto_commit: AutoCommit::OnSync,
+            ..Default::default()
+        };
+
+        // Modes are written in snake case
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("auto_commit = \"on_sync\""));
+
+        let deserialized: GitConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(deserialized.auto_commit, AutoCommit::OnSync);
+    }
+
+    #[test]
+    fn test_sync_mode_serialization() {
+        let config = GitConfig {
+            sync_mode: SyncMode::Rebase,
+            ..Default::default()
+        };
+
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("sync_mode = \"rebase\""));
+
+        let deserialized: GitConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(deserialized.sync_mode, SyncMode::Rebase);
+    }
+
+    #[test]
+    fn test_auto_sync_settings() {
+        let config = GitConfig {
+            auto_sync: AutoSync::OnWrite,
+            ..Default::default()
+        };
+
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("auto_sync = \"on_write\""));
+
+        let deserialized: GitConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(deserialized.auto_sync, AutoSync::OnWrite);
+
+        // Unset durations fall back to the defaults
+        assert_eq!(config.sync_interval(), Duration::from_secs(15 * 60));
+        assert_eq!(config.sync_timeout(), Duration::from_secs(10));
+
+        let config = GitConfig {
+            auto_sync_interval: 60,
+            auto_sync_timeout: 3,
+            ..Default::default()
+        };
+        assert_eq!(config.sync_interval(), Duration::from_secs(60 * 60));
+        assert_eq!(config.sync_timeout(), Duration::from_secs(3));
+    }
 }
@@ -1,9 +1,10 @@
 use std::{collections::BTreeMap, fs, path::Path};
 
-use anyhow::{Context, Result};
+use anyhow::Context;
 use toml_edit::{DocumentMut, Item, Table};
 
-use super::{Config, ConfigReflection};
+use super::{Config, ConfigReflection, DEFAULT_WORKSPACE, dirs};
+use crate::{Error, Result};
 
 pub struct ConfigManager {
     config_path: String,
@@ -11,24 +12,23 @@ pub struct ConfigManager {
 
 impl ConfigManager {
     pub fn new() -> Result<Self> {
-        let pkg_name = env!("CARGO_PKG_NAME")
-            .split_once('-')
-            .map_or(env!("CARGO_PKG_NAME"), |(name, _)| name)
-            .to_string();
-        let config_path = format!("~/.{pkg_name}/config.toml");
-        let config_path = shellexpand::tilde(&config_path).into_owned();
-
-        Ok(Self { config_path })
+        Ok(Self::with_path(&Config::default_config_file()))
+    }
+
+    /// Manage the given configuration file instead of the default one
+    pub fn with_path(config_path: &Path) -> Self {
+        let config_path = shellexpand::tilde(&config_path.to_string_lossy()).into_owned();
+        Self { config_path }
     }
 
     fn read_config_file(&self) -> Result<String> {
-        fs::read_to_string(&self.config_path)
-            .with_context(|| format!("Failed to read config file: {}", self.config_path))
+        Ok(fs::read_to_string(&self.config_path)
+            .with_context(|| format!("Failed to read config file: {}", self.config_path))?)
     }
 
     fn validate_config_key(&self, key: &str) -> Result<()> {
         if !Config::is_valid_path(key) {
-            anyhow::bail!("Invalid configuration key: {key}");
+            return Err(Error::Config(format!("Invalid configuration key: {key}")));
         }
         Ok(())
     }
@@ -49,7 +49,8 @@ impl ConfigManager {
         }
 
         // Fall back to current config using reflection
-        let config = Config::new().with_context(|| "Failed to load current configuration")?;
+        let config = Config::from_file(Path::new(&self.config_path))
+            .with_context(|| "Failed to load current configuration")?;
         config.get_field_value(key)
     }
 
@@ -102,6 +103,70 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Add a named workspace with its own root directory
+    pub fn add_workspace(&self, name: &str, root_dir: &str) -> Result<()> {
+        if name.is_empty() || name == DEFAULT_WORKSPACE || name.contains('.') {
+            return Err(Error::Config(format!("Invalid workspace name: {name}")));
+        }
+        let config_dir = Path::new(&self.config_path).parent().unwrap();
+        fs::create_dir_all(config_dir).with_context(|| {
+            format!(
+                "Failed to create config directory: {}",
+                config_dir.display()
+            )
+        })?;
+        let mut doc = if Path::new(&self.config_path).exists() {
+            self.read_config_file()?
+                .parse::<DocumentMut>()
+                .with_context(|| format!("Failed to parse config file: {}", self.config_path))?
+        } else {
+            DocumentMut::new()
+        };
+
+        if self.has_workspace(&doc, name) {
+            return Err(Error::Config(format!("Workspace '{name}' already exists")));
+        }
+        if !doc.contains_key("workspaces") {
+            let mut workspaces = Table::new();
+            workspaces.set_implicit(true);
+            doc["workspaces"] = Item::Table(workspaces);
+        }
+        let workspaces = doc["workspaces"]
+            .as_table_mut()
+            .ok_or_else(|| Error::Config("Section 'workspaces' is not a table".to_string()))?;
+        let mut workspace = Table::new();
+        workspace["root_dir"] = toml_edit::value(root_dir);
+        workspaces[name] = Item::Table(workspace);
+
+        fs::write(&self.config_path, doc.to_string())
+            .with_context(|| format!("Failed to write config file: {}", self.config_path))?;
+
+        Ok(())
+    }
+
+    /// Switch to another workspace
+    pub fn use_workspace(&self, name: &str) -> Result<()> {
+        if name == DEFAULT_WORKSPACE {
+            return self.unset_config_value("workspace.current");
+        }
+        let doc = if Path::new(&self.config_path).exists() {
+            self.read_config_file()?.parse::<DocumentMut>().ok()
+        } else {
+            None
+        };
+        if !doc.is_some_and(|doc| self.has_workspace(&doc, name)) {
+            return Err(Error::Config(format!("No workspace named '{name}'")));
+        }
+        self.set_config_value("workspace.current", name)
+    }
+
+    /// Check if the config file defines a workspace
+    fn has_workspace(&self, doc: &DocumentMut, name: &str) -> bool {
+        doc.get("workspaces")
+            .and_then(Item::as_table_like)
+            .is_some_and(|workspaces| workspaces.contains_key(name))
+    }
+
     /// List configuration values showing user-configured values over defaults
     pub fn list_config_values(&self) -> Result<BTreeMap<String, String>> {
         let default_config = Config::default();
@@ -141,10 +206,7 @@ impl ConfigManager {
         }
 
         // Then check environment variables
-        let pkg_name = env!("CARGO_PKG_NAME")
-            .split_once('-')
-            .map_or(env!("CARGO_PKG_NAME"), |(name, _)| name)
-            .to_uppercase();
+        let pkg_name = dirs::app_name().to_uppercase();
         let env_var = format!("{pkg_name}_{}", key.replace('.', "__").to_uppercase());
 
         Ok(std::env::var(&env_var).ok())
@@ -152,14 +214,16 @@ impl ConfigManager {
 
     /// Get the effective configuration (for completion and runtime use)
     pub fn get_effective_config(&self) -> Result<Config> {
-        Config::new()
+        Config::from_file(Path::new(&self.config_path))
     }
 
     fn set_value_in_file(&self, doc: &mut DocumentMut, key: &str, value: &str) -> Result<()> {
         let parts = key.split('.').collect::<Vec<_>>();
 
         let &[section, field] = parts.as_slice() else {
-            anyhow::bail!("Invalid configuration key format: {key}");
+            return Err(Error::Config(format!(
+                "Invalid configuration key format: {key}"
+            )));
         };
 
         // Validate using reflection
@@ -171,7 +235,7 @@ impl ConfigManager {
 
         let table = doc[section]
             .as_table_mut()
-            .ok_or_else(|| anyhow::anyhow!("Section '{section}' is not a table"))?;
+            .ok_or_else(|| Error::Config(format!("Section '{section}' is not a table")))?;
 
         // Use reflection to parse the value
         let parsed_value = Config::parse_field_value(key, value)?;
@@ -213,7 +277,9 @@ impl ConfigManager {
         let parts = key.split('.').collect::<Vec<_>>();
 
         let &[section, field] = parts.as_slice() else {
-            anyhow::bail!("Invalid configuration key format: {key}");
+            return Err(Error::Config(format!(
+                "Invalid configuration key format: {key}"
+            )));
         };
 
         if let Some(section_item) = doc.get_mut(section)
@@ -256,6 +322,45 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_workspaces() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let manager = ConfigManager {
+            config_path: config_path.to_str().unwrap().to_string(),
+        };
+
+        assert!(manager.use_workspace("team").is_err());
+        manager.add_workspace("team", "~/team").unwrap();
+        assert!(manager.add_workspace("team", "~/other").is_err());
+        assert!(manager.add_workspace(DEFAULT_WORKSPACE, "~/other").is_err());
+
+        manager.use_workspace("team").unwrap();
+        let config = Config::load(
+            Config::default(),
+            config_path.to_str().unwrap(),
+            None,
+            "RUTD_TEST_MANAGER_WS_",
+        )
+        .unwrap();
+        assert_eq!(config.workspace.current, "team");
+        assert_eq!(
+            config.workspaces["team"].root_dir,
+            std::path::PathBuf::from("~/team")
+        );
+
+        // Back to the default workspace
+        manager.use_workspace(DEFAULT_WORKSPACE).unwrap();
+        let config = Config::load(
+            Config::default(),
+            config_path.to_str().unwrap(),
+            None,
+            "RUTD_TEST_MANAGER_WS_",
+        )
+        .unwrap();
+        assert_eq!(config.workspace_name(None), DEFAULT_WORKSPACE);
+    }
+
     #[test]
     fn test_set_config_value() {
         let temp_dir = tempdir().unwrap();
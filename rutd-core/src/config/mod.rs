@@ -1,25 +1,49 @@
+pub mod dirs;
 pub mod git;
 pub mod logging;
 pub mod manager;
 pub mod path;
 pub mod reflection;
 pub mod task;
+pub mod workspace;
 
-use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+pub use dirs::BaseDirs;
 use figment::{
     Figment,
     providers::{Env, Format, Serialized, Toml},
 };
-pub use git::GitConfig;
+pub use git::{AutoCommit, AutoSync, CommitSigning, GitConfig, SyncMode};
 pub use logging::LogConfig;
 pub use manager::ConfigManager;
 pub use path::PathConfig;
 pub use reflection::ConfigReflection;
 use serde::{Deserialize, Serialize};
 pub use task::TaskConfig;
+pub use workspace::{DEFAULT_WORKSPACE, Workspace, WorkspaceConfig};
+
+use crate::{Error, Result};
+
+/// Name of the project configuration file
+pub const PROJECT_CONFIG_FILE: &str = ".rutd.toml";
+/// Name of the project directory holding a configuration file
+pub const PROJECT_CONFIG_DIR: &str = ".rutd";
+/// Settings a project configuration file may set, by section, all the
+/// settings of a section being allowed if no key is given
+///
+/// Project files come with any checked out repository, so they must not run
+/// commands, or change credentials, signing, remotes, workspaces or logs.
+const PROJECT_CONFIG_KEYS: &[(&str, &[&str])] =
+    &[("task", &[]), ("path", &["root_dir", "state_dir"])];
 
 /// Main configuration structure that holds all configuration options
-#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Config {
     /// Path configuration
     pub path: PathConfig,
@@ -29,6 +53,10 @@ pub struct Config {
     pub log: LogConfig,
     /// Task configuration
     pub task: TaskConfig,
+    /// Workspace selection
+    pub workspace: WorkspaceConfig,
+    /// Named workspaces, besides the default one
+    pub workspaces: BTreeMap<String, Workspace>,
 }
 
 impl Config {
@@ -36,30 +64,164 @@ impl Config {
     ///
     /// Configurations are loaded in the following precedence:
     /// 1. Environment variables
-    /// 2. Configuration file
-    /// 3. Default values
+    /// 2. Project configuration file, see [`Config::find_project_config`]
+    /// 3. Configuration file, see [`Config::default_config_file`]
+    /// 4. Default values, following [`BaseDirs`]
     pub fn new() -> Result<Self> {
-        // Get the package name from the compiled binary name
-        // This should be resolved to `rutd` for all binaries
-        // (e.g. `rutd`, `rutd-cli`, `rutd-tui`, etc.)
-        let pkg_name = env!("CARGO_PKG_NAME")
-            .split_once('-')
-            .map_or(env!("CARGO_PKG_NAME"), |(name, _)| name)
-            .to_string();
-        let config_file = format!("~/.{pkg_name}/config.toml");
-        let env_var_prefix = pkg_name.to_uppercase() + "_";
+        Self::from_file(&Self::default_config_file())
+    }
+
+    /// Get configurations, reading the given configuration file instead of
+    /// the default one
+    pub fn from_file(config_file: &Path) -> Result<Self> {
+        let config_file = shellexpand::tilde(&config_file.to_string_lossy()).into_owned();
+        let env_var_prefix = dirs::app_name().to_uppercase() + "_";
+
+        // Keep the tasks and the state in their base directories by default
+        let defaults = Self {
+            path: PathConfig::from_base_dirs(&BaseDirs::new()),
+            ..Default::default()
+        };
+
+        // Look for a project configuration from the current directory
+        let project_file = Self::current_project_config(Path::new(&config_file));
 
         // Load the configuration
-        Self::load(&config_file, &env_var_prefix)
+        Self::load(
+            defaults,
+            &config_file,
+            project_file.as_deref(),
+            &env_var_prefix,
+        )
+    }
+
+    /// Path of the configuration file, in the configuration base directory
+    pub fn default_config_file() -> PathBuf {
+        BaseDirs::new().config_file()
+    }
+
+    /// Find the project configuration file from the current directory
+    pub fn current_project_config(user_config: &Path) -> Option<PathBuf> {
+        env::current_dir()
+            .ok()
+            .and_then(|dir| Self::find_project_config(&dir, user_config))
+    }
+
+    /// Find the project configuration file in a directory or its parents
+    ///
+    /// The closest `.rutd.toml` file or `.rutd/config.toml` file is used,
+    /// except the user configuration file.
+    pub fn find_project_config(start: &Path, user_config: &Path) -> Option<PathBuf> {
+        start.ancestors().find_map(|dir| {
+            [
+                dir.join(PROJECT_CONFIG_FILE),
+                dir.join(PROJECT_CONFIG_DIR).join("config.toml"),
+            ]
+            .into_iter()
+            .find(|path| path.is_file() && path != user_config)
+        })
+    }
+
+    /// Read a project configuration file
+    ///
+    /// A relative `path.root_dir` or `path.state_dir` is relative to the
+    /// project directory, the one holding `.rutd.toml` or `.rutd/`. The state
+    /// is kept in the root directory unless `path.state_dir` is set.
+    ///
+    /// Settings missing from [`PROJECT_CONFIG_KEYS`] are ignored with a
+    /// warning.
+    fn load_project_config(path: &Path) -> Result<toml::Table> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read project config file: {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse project config file: {}", path.display()))?;
+        let mut table = Self::allowed_project_settings(table, |key| {
+            // Logging is not set up while loading the configuration
+            eprintln!(
+                "Warning: ignoring `{key}` in project config file {}",
+                path.display()
+            );
+        });
+
+        let project_dir = if path.ends_with(PROJECT_CONFIG_FILE) {
+            path.parent()
+        } else {
+            path.parent().and_then(Path::parent)
+        }
+        .unwrap_or(Path::new("."));
+        if let Some(path) = table.get_mut("path").and_then(toml::Value::as_table_mut) {
+            for key in ["root_dir", "state_dir"] {
+                if let Some(value) = path.get_mut(key)
+                    && let Some(dir) = value.as_str()
+                {
+                    let dir = project_dir.join(shellexpand::tilde(dir).as_ref());
+                    *value = toml::Value::String(dir.to_string_lossy().into_owned());
+                }
+            }
+            if path.contains_key("root_dir") && !path.contains_key("state_dir") {
+                path.insert("state_dir".to_string(), toml::Value::String(String::new()));
+            }
+        }
+        Ok(table)
+    }
+
+    /// Keep the settings of a project configuration allowed by
+    /// [`PROJECT_CONFIG_KEYS`], calling `ignore` with the others
+    fn allowed_project_settings(table: toml::Table, mut ignore: impl FnMut(&str)) -> toml::Table {
+        let mut allowed = toml::Table::new();
+        for (section, value) in table {
+            let Some(&(_, keys)) = PROJECT_CONFIG_KEYS
+                .iter()
+                .find(|(name, _)| *name == section)
+            else {
+                ignore(&section);
+                continue;
+            };
+            let toml::Value::Table(settings) = value else {
+                ignore(&section);
+                continue;
+            };
+            let settings = settings
+                .into_iter()
+                .filter(|(key, _)| {
+                    let is_allowed = keys.is_empty() || keys.contains(&key.as_str());
+                    if !is_allowed {
+                        ignore(&format!("{section}.{key}"));
+                    }
+                    is_allowed
+                })
+                .collect();
+            allowed.insert(section, toml::Value::Table(settings));
+        }
+        allowed
     }
 
     /// This function loads the configuration from a file and environment
     /// variables
     ///
+    /// The state is kept in the default state directory only if the root
+    /// directory is not changed, and in the root directory otherwise unless
+    /// `path.state_dir` is set.
+    ///
     /// Also useful for testing purposes
-    fn load(config_path: &str, env_var_prefix: &str) -> Result<Self> {
+    fn load(
+        defaults: Self,
+        config_path: &str,
+        project_path: Option<&Path>,
+        env_var_prefix: &str,
+    ) -> Result<Self> {
+        // Leave the state directory unset to tell whether it is configured
+        let default_paths = defaults.path.clone();
+        let defaults = Self {
+            path: PathConfig {
+                state_dir: PathBuf::new(),
+                ..defaults.path
+            },
+            ..defaults
+        };
+
         // Create a base Figment with default values
-        let mut figment = Figment::new().merge(Serialized::defaults(Self::default()));
+        let mut figment = Figment::new().merge(Serialized::defaults(defaults));
 
         // Only attempt to load from config file if it exists
         let path = std::path::PathBuf::from(config_path);
@@ -67,6 +229,17 @@ impl Config {
             figment = figment.merge(Toml::file(config_path));
         }
 
+        // Then the project configuration file, if any
+        if let Some(project_path) = project_path {
+            log::debug!(
+                "Loading project configuration from {}",
+                project_path.display()
+            );
+            figment = figment.merge(Serialized::defaults(Self::load_project_config(
+                project_path,
+            )?));
+        }
+
         // Add environment variables
         figment = figment.merge(Env::prefixed(env_var_prefix).map(|key| {
             // Convert environment variable keys to a format that matches the config
@@ -79,7 +252,56 @@ impl Config {
         }));
 
         // Extract the config
-        Ok(figment.extract()?)
+        let mut config: Self = figment
+            .extract()
+            .map_err(|e| Error::Config(e.to_string()))?;
+        if config.path.state_dir.as_os_str().is_empty()
+            && config.path.root_dir == default_paths.root_dir
+        {
+            config.path.state_dir = default_paths.state_dir;
+        }
+        Ok(config)
+    }
+
+    /// Names of the workspaces, starting with the default one
+    pub fn workspace_names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_WORKSPACE.to_string())
+            .chain(self.workspaces.keys().cloned())
+            .collect()
+    }
+
+    /// Name of the workspace in use, given on the command line or configured
+    /// in `workspace.current`
+    pub fn workspace_name<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.or(Some(self.workspace.current.as_str()))
+            .filter(|name| !name.is_empty())
+            .unwrap_or(DEFAULT_WORKSPACE)
+    }
+
+    /// Use the root directory and git settings of a workspace
+    ///
+    /// Git settings of the workspace override the ones of the `git` section,
+    /// and its state is kept in its root directory. Fails if no workspace has
+    /// this name.
+    pub fn with_workspace(mut self, name: &str) -> Result<Self> {
+        if name == DEFAULT_WORKSPACE {
+            return Ok(self);
+        }
+        let workspace = self
+            .workspaces
+            .get(name)
+            .ok_or_else(|| Error::Config(format!("No workspace named '{name}'")))?;
+
+        let root_dir = workspace.root_dir.to_string_lossy();
+        self.path.root_dir = shellexpand::tilde(&root_dir).as_ref().into();
+        self.path.state_dir = PathBuf::new();
+
+        let mut git = toml::Table::try_from(&self.git)?;
+        git.extend(workspace.git.clone());
+        self.git = git
+            .try_into()
+            .with_context(|| format!("Invalid git settings in workspace '{name}'"))?;
+        Ok(self)
     }
 }
 
@@ -198,7 +420,9 @@ mod tests {
 
         // Load the config with a specific test prefix to avoid interference
         let config = Config::load(
+            Config::default(),
             config_path.to_str().unwrap(),
+            None,
             "RUTD_TEST_CONFIG_FILE_LOADING_",
         );
 
@@ -225,6 +449,52 @@ mod tests {
         assert_eq!(config.task.types, vec!["test-type-1", "test-type-2"]);
     }
 
+    #[test]
+    fn test_config_state_dir() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let defaults = || Config {
+            path: PathConfig::from_base_dirs(&BaseDirs {
+                config_dir: PathBuf::from("/config/rutd"),
+                data_dir: PathBuf::from("/data/rutd"),
+                state_dir: PathBuf::from("/state/rutd"),
+            }),
+            ..Default::default()
+        };
+        let load = |content: &str| {
+            fs::write(&config_path, content).unwrap();
+            Config::load(
+                defaults(),
+                config_path.to_str().unwrap(),
+                None,
+                "RUTD_TEST_CONFIG_STATE_DIR_",
+            )
+            .unwrap()
+        };
+
+        // The state is kept in its base directory by default
+        let config = load("");
+        assert_eq!(config.path.state_path(), Path::new("/state/rutd"));
+
+        // But with the tasks when only the root directory is set
+        let config = load("[path]\nroot_dir = \"/custom/root\"\n");
+        assert_eq!(
+            config.path.active_task_file_path(),
+            Path::new("/custom/root/active_task.toml")
+        );
+        assert_eq!(
+            config.path.log_file_path(),
+            Path::new("/custom/root/rutd.log")
+        );
+
+        // Unless the state directory is set as well
+        let config = load("[path]\nroot_dir = \"/custom/root\"\nstate_dir = \"/custom/state\"\n");
+        assert_eq!(
+            config.path.active_task_file_path(),
+            Path::new("/custom/state/active_task.toml")
+        );
+    }
+
     #[test]
     fn test_env_var_config_loading() {
         let mut guard = EnvVarGuard::new();
@@ -240,7 +510,12 @@ mod tests {
         );
 
         // Load config (with a non-existent file path to test env-only configuration)
-        let config = Config::load("does-not-exist.toml", "RUTD_TEST_ENV_CONFIG_");
+        let config = Config::load(
+            Config::default(),
+            "does-not-exist.toml",
+            None,
+            "RUTD_TEST_ENV_CONFIG_",
+        );
         assert!(config.is_ok());
 
         let config = config.unwrap();
@@ -282,7 +557,9 @@ mod tests {
 
         // Load the config with the environment variable prefix
         let config = Config::load(
+            Config::default(),
             config_path.to_str().unwrap(),
+            None,
             "RUTD_TEST_CONFIG_PRECEDENCE_",
         );
 
@@ -297,7 +574,7 @@ mod tests {
 
     #[test]
     fn test_config_new_with_env_vars() {
-        // This test directly tests the Config::new() function instead of Config::load()
+        // This test directly tests the Config::new() function instead of Config::load(Config::default(), )
         let mut guard = EnvVarGuard::new();
 
         // Set environment variables that should be picked up by Config::new()
@@ -324,10 +601,196 @@ mod tests {
         guard.set("RUTD_TEST_INVALID_LOG__CONSOLE", "not-a-bool");
 
         // Loading should fail
-        let config = Config::load("does-not-exist.toml", "RUTD_TEST_INVALID_");
+        let config = Config::load(
+            Config::default(),
+            "does-not-exist.toml",
+            None,
+            "RUTD_TEST_INVALID_",
+        );
         assert!(config.is_err());
     }
 
+    #[test]
+    fn test_workspaces() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let config_content = r#"
+        [path]
+        root_dir = "/personal"
+
+        [git]
+        username = "user"
+        remote = "origin"
+
+        [workspace]
+        current = "team"
+
+        [workspaces.team]
+        root_dir = "/team"
+        git = { remote = "team", auto_sync = "on_write" }
+        "#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = Config::load(
+            Config::default(),
+            config_path.to_str().unwrap(),
+            None,
+            "RUTD_TEST_WORKSPACES_",
+        )
+        .unwrap();
+        assert_eq!(config.workspace_names(), vec!["default", "team"]);
+        assert_eq!(config.workspace_name(None), "team");
+        assert_eq!(config.workspace_name(Some("default")), "default");
+
+        // Settings of the workspace override the shared ones
+        let team = config.clone().with_workspace("team").unwrap();
+        assert_eq!(team.path.root_dir, PathBuf::from("/team"));
+        assert_eq!(team.git.remote, "team");
+        assert_eq!(team.git.auto_sync, AutoSync::OnWrite);
+        assert_eq!(team.git.username, "user");
+
+        let personal = config.clone().with_workspace(DEFAULT_WORKSPACE).unwrap();
+        assert_eq!(personal.path.root_dir, PathBuf::from("/personal"));
+        assert_eq!(personal.git.remote, "origin");
+
+        assert!(config.with_workspace("missing").is_err());
+    }
+
+    #[test]
+    fn test_project_config() {
+        let temp_dir = tempdir().unwrap();
+        let user_config = temp_dir.path().join("user.toml");
+        fs::write(
+            &user_config,
+            "[task]\ndefault_scope = \"user\"\ndefault_type = \"feat\"\n",
+        )
+        .unwrap();
+        let project_dir = temp_dir.path().join("project");
+        let nested_dir = project_dir.join("src").join("nested");
+        fs::create_dir_all(&nested_dir).unwrap();
+
+        // Nothing to find
+        assert!(Config::find_project_config(&nested_dir, &user_config).is_none());
+
+        // The closest project file is found from nested directories
+        let project_file = project_dir.join(PROJECT_CONFIG_FILE);
+        fs::write(
+            &project_file,
+            "[path]\nroot_dir = \".tasks\"\n\n[task]\ndefault_scope = \"project\"\n",
+        )
+        .unwrap();
+        let found = Config::find_project_config(&nested_dir, &user_config).unwrap();
+        assert_eq!(found, project_file);
+
+        // Project settings override the user ones, and paths are relative to
+        // the project, keeping the state with the tasks
+        let defaults = Config {
+            path: PathConfig {
+                state_dir: PathBuf::from("/state"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let config = Config::load(
+            defaults,
+            user_config.to_str().unwrap(),
+            Some(&found),
+            "RUTD_TEST_PROJECT_CONFIG_",
+        )
+        .unwrap();
+        assert_eq!(config.task.default_scope, "project");
+        assert_eq!(config.task.default_type, "feat");
+        assert_eq!(config.path.root_dir, project_dir.join(".tasks"));
+        assert_eq!(config.path.state_path(), project_dir.join(".tasks"));
+
+        // A configuration directory works as well
+        let config_dir = nested_dir.join(PROJECT_CONFIG_DIR);
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.toml"),
+            "[path]\nroot_dir = \"tasks\"\n",
+        )
+        .unwrap();
+        let found = Config::find_project_config(&nested_dir, &user_config).unwrap();
+        let config = Config::load(
+            Config::default(),
+            user_config.to_str().unwrap(),
+            Some(&found),
+            "RUTD_TEST_PROJECT_CONFIG_",
+        )
+        .unwrap();
+        assert_eq!(config.path.root_dir, nested_dir.join("tasks"));
+
+        // But the user configuration file is not a project one
+        assert!(
+            Config::find_project_config(&nested_dir, &config_dir.join("config.toml"))
+                .is_some_and(|path| path == project_file)
+        );
+    }
+
+    #[test]
+    fn test_project_config_allowed_settings() {
+        let temp_dir = tempdir().unwrap();
+        let project_file = temp_dir.path().join(PROJECT_CONFIG_FILE);
+        fs::write(
+            &project_file,
+            r#"
+            [task]
+            default_scope = "project"
+
+            [path]
+            root_dir = "tasks"
+            log_file = "/etc/passwd"
+
+            [git]
+            password_command = "touch pwned"
+            ssh_key = "~/.ssh/other"
+
+            [workspace]
+            current = "other"
+
+            [workspaces.other]
+            root_dir = "/other"
+
+            [log]
+            console = true
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load(
+            Config::default(),
+            "does-not-exist.toml",
+            Some(&project_file),
+            "RUTD_TEST_PROJECT_ALLOWED_",
+        )
+        .unwrap();
+
+        // Task and root directory settings are applied
+        assert_eq!(config.task.default_scope, "project");
+        assert_eq!(config.path.root_dir, temp_dir.path().join("tasks"));
+
+        // But nothing else
+        let defaults = Config::default();
+        assert!(config.git.password_command.is_empty());
+        assert_eq!(config.git, defaults.git);
+        assert_eq!(config.path.log_file, defaults.path.log_file);
+        assert_eq!(config.workspace, defaults.workspace);
+        assert!(config.workspaces.is_empty());
+        assert_eq!(config.log, defaults.log);
+
+        // And ignored settings are reported
+        let table: toml::Table =
+            toml::from_str(&fs::read_to_string(&project_file).unwrap()).unwrap();
+        let mut ignored = Vec::new();
+        Config::allowed_project_settings(table, |key| ignored.push(key.to_string()));
+        ignored.sort();
+        assert_eq!(
+            ignored,
+            vec!["git", "log", "path.log_file", "workspace", "workspaces"]
+        );
+    }
+
     #[test]
     fn test_config_debug_representation() {
         let config = Config {
@@ -335,9 +798,11 @@ mod tests {
             git: GitConfig {
                 username: "debug-user".to_string(),
                 password: "debug-password".to_string(),
+                ..Default::default()
             },
             log: LogConfig::default(),
             task: TaskConfig::default(),
+            ..Default::default()
         };
 
         // Check the debug representation contains the expected content
@@ -1,12 +1,14 @@
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
-use shellexpand::tilde;
+
+use super::BaseDirs;
 
 /// Default path constants
-pub const DEFAULT_ROOT_DIR: &str = "~/.rutd";
 pub const DEFAULT_TASKS_DIR: &str = "tasks";
 pub const DEFAULT_ACTIVE_FILE: &str = "active_task.toml";
+pub const DEFAULT_WORKING_SET_FILE: &str = "working_set.toml";
+pub const DEFAULT_PENDING_SYNC_FILE: &str = "pending_sync.toml";
 pub const DEFAULT_LOG_FILE: &str = "rutd.log";
 
 /// Path configuration management
@@ -14,45 +16,85 @@ pub const DEFAULT_LOG_FILE: &str = "rutd.log";
 pub struct PathConfig {
     /// Root directory path
     pub root_dir: PathBuf,
+    /// State directory path, holding the active task, working set, pending
+    /// sync and log files
+    ///
+    /// Falls back to the root directory if empty.
+    pub state_dir: PathBuf,
     /// Tasks directory path
     pub tasks_dir: PathBuf,
     /// Active task file path
     pub active_task_file: PathBuf,
+    /// Working set file path, holding the short numbers of working tasks
+    pub working_set_file: PathBuf,
+    /// Pending sync file path, recording an automatic sync to retry
+    pub pending_sync_file: PathBuf,
     /// Log file path
     pub log_file: PathBuf,
 }
 
 impl Default for PathConfig {
     fn default() -> Self {
-        let root_dir = PathBuf::from(tilde(DEFAULT_ROOT_DIR).as_ref());
+        let root_dir = BaseDirs::new().data_dir;
+        let state_dir = PathBuf::new();
         let tasks_dir = PathBuf::from(DEFAULT_TASKS_DIR);
         let active_task_file = PathBuf::from(DEFAULT_ACTIVE_FILE);
+        let working_set_file = PathBuf::from(DEFAULT_WORKING_SET_FILE);
+        let pending_sync_file = PathBuf::from(DEFAULT_PENDING_SYNC_FILE);
         let log_file = PathBuf::from(DEFAULT_LOG_FILE);
 
         Self {
             root_dir,
+            state_dir,
             tasks_dir,
             active_task_file,
+            working_set_file,
+            pending_sync_file,
             log_file,
         }
     }
 }
 
 impl PathConfig {
+    /// Paths of the base directories, keeping the state apart from the tasks
+    pub fn from_base_dirs(dirs: &BaseDirs) -> Self {
+        Self {
+            root_dir: dirs.data_dir.clone(),
+            state_dir: dirs.state_dir.clone(),
+            ..Default::default()
+        }
+    }
+
     pub fn root_path(&self) -> PathBuf {
         self.root_dir.clone()
     }
 
+    pub fn state_path(&self) -> PathBuf {
+        if self.state_dir.as_os_str().is_empty() {
+            self.root_path()
+        } else {
+            self.state_dir.clone()
+        }
+    }
+
     pub fn task_dir_path(&self) -> PathBuf {
         self.root_dir.join(&self.tasks_dir)
     }
 
     pub fn active_task_file_path(&self) -> PathBuf {
-        self.root_dir.join(&self.active_task_file)
+        self.state_path().join(&self.active_task_file)
+    }
+
+    pub fn working_set_file_path(&self) -> PathBuf {
+        self.state_path().join(&self.working_set_file)
+    }
+
+    pub fn pending_sync_file_path(&self) -> PathBuf {
+        self.state_path().join(&self.pending_sync_file)
     }
 
     pub fn log_file_path(&self) -> PathBuf {
-        self.root_dir.join(&self.log_file)
+        self.state_path().join(&self.log_file)
     }
 }
 
@@ -67,7 +109,11 @@ mod tests {
         let config = PathConfig::default();
 
         // Check root directory
-        assert!(config.root_dir.to_string_lossy().contains(".rutd"));
+        assert!(config.root_dir.to_string_lossy().contains("rutd"));
+
+        // Check state is kept in the root directory
+        assert!(config.state_dir.as_os_str().is_empty());
+        assert_eq!(config.state_path(), config.root_dir);
 
         // Check tasks directory is "tasks"
         assert_eq!(config.tasks_dir, PathBuf::from("tasks"));
@@ -75,6 +121,12 @@ mod tests {
         // Check active task file is "active_task.toml"
         assert_eq!(config.active_task_file, PathBuf::from("active_task.toml"));
 
+        // Check working set file is "working_set.toml"
+        assert_eq!(config.working_set_file, PathBuf::from("working_set.toml"));
+
+        // Check pending sync file is "pending_sync.toml"
+        assert_eq!(config.pending_sync_file, PathBuf::from("pending_sync.toml"));
+
         // Check log file is "rutd.log"
         assert_eq!(config.log_file, PathBuf::from("rutd.log"));
     }
@@ -123,8 +175,11 @@ mod tests {
         // Create a custom path configuration
         let config = PathConfig {
             root_dir: PathBuf::from("/custom/root"),
+            state_dir: PathBuf::new(),
             tasks_dir: PathBuf::from("custom_tasks"),
             active_task_file: PathBuf::from("custom_active.toml"),
+            working_set_file: PathBuf::from("custom_working_set.toml"),
+            pending_sync_file: PathBuf::from("custom_pending_sync.toml"),
             log_file: PathBuf::from("custom.log"),
         };
 
@@ -137,6 +192,38 @@ mod tests {
             config.active_task_file_path(),
             Path::new("/custom/root/custom_active.toml")
         );
+        assert_eq!(
+            config.working_set_file_path(),
+            Path::new("/custom/root/custom_working_set.toml")
+        );
+        assert_eq!(
+            config.pending_sync_file_path(),
+            Path::new("/custom/root/custom_pending_sync.toml")
+        );
         assert_eq!(config.log_file_path(), Path::new("/custom/root/custom.log"));
     }
+
+    #[test]
+    fn test_state_paths() {
+        let dirs = BaseDirs {
+            config_dir: PathBuf::from("/config/rutd"),
+            data_dir: PathBuf::from("/data/rutd"),
+            state_dir: PathBuf::from("/state/rutd"),
+        };
+        let config = PathConfig::from_base_dirs(&dirs);
+
+        // Tasks are data
+        assert_eq!(config.task_dir_path(), Path::new("/data/rutd/tasks"));
+
+        // The rest is state
+        assert_eq!(
+            config.active_task_file_path(),
+            Path::new("/state/rutd/active_task.toml")
+        );
+        assert_eq!(
+            config.working_set_file_path(),
+            Path::new("/state/rutd/working_set.toml")
+        );
+        assert_eq!(config.log_file_path(), Path::new("/state/rutd/rutd.log"));
+    }
 }
@@ -1,8 +1,9 @@
-use anyhow::{Context, Result};
+use anyhow::Context;
 use serde_json::Value as JsonValue;
 use toml_edit::{Array, Formatted, Value as TomlValue};
 
 use super::Config;
+use crate::{Error, Result};
 
 /// Information about a configuration field discovered through introspection
 #[derive(Clone, Debug)]
@@ -58,7 +59,7 @@ impl ConfigReflection for Config {
         let field_info = discover_all_paths(&json_value)
             .into_iter()
             .find(|info| info.path == path)
-            .ok_or_else(|| anyhow::anyhow!("Unknown configuration key: {path}"))?;
+            .ok_or_else(|| Error::Config(format!("Unknown configuration key: {path}")))?;
 
         // Parse based on the detected type
         parse_value_by_type(&field_info.value_type, value_str, path)
@@ -193,7 +194,10 @@ fn format_json_value_for_display(value: &JsonValue) -> Result<String> {
             Ok(format!("[{}]", items.join(", ")))
         }
         JsonValue::Null => Ok(String::new()),
-        JsonValue::Object(_) => Ok(serde_json::to_string_pretty(value)?),
+        JsonValue::Object(_) => {
+            Ok(serde_json::to_string_pretty(value)
+                .context("Failed to format configuration value")?)
+        }
     }
 }
 
@@ -208,6 +212,7 @@ mod tests {
             git: GitConfig {
                 username: "test-user".to_string(),
                 password: "secret".to_string(),
+                ..Default::default()
             },
             log: LogConfig {
                 console: true,
@@ -216,7 +221,9 @@ mod tests {
             task: TaskConfig {
                 scopes: vec!["backend".to_string(), "frontend".to_string()],
                 types: vec!["feat".to_string(), "fix".to_string()],
+                ..Default::default()
             },
+            ..Default::default()
         }
     }
 
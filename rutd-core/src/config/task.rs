@@ -1,5 +1,12 @@
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    Error, Result,
+    task::{Filter, TaskStatus},
+};
+
 const DEFAULT_SCOPES: [&str; 1] = ["other"];
 const DEFAULT_TYPES: [&str; 10] = [
     "feat", "fix", "build", "chore", "ci", "docs", "style", "refactor", "perf", "test",
@@ -12,6 +19,16 @@ pub struct TaskConfig {
     pub scopes: Vec<String>,
     /// Pinned task types for autocompletion
     pub types: Vec<String>,
+    /// Scope of new tasks when none is given
+    pub default_scope: String,
+    /// Type of new tasks when none is given
+    pub default_type: String,
+    /// Scope of the tasks listed when no scope filter is given
+    pub list_scope: String,
+    /// Type of the tasks listed when no type filter is given
+    pub list_type: String,
+    /// Status of the tasks listed when no status filter is given
+    pub list_status: String,
 }
 
 impl Default for TaskConfig {
@@ -19,10 +36,35 @@ impl Default for TaskConfig {
         Self {
             scopes: DEFAULT_SCOPES.iter().map(|&s| s.to_string()).collect(),
             types: DEFAULT_TYPES.iter().map(|&s| s.to_string()).collect(),
+            default_scope: String::new(),
+            default_type: String::new(),
+            list_scope: String::new(),
+            list_type: String::new(),
+            list_status: String::new(),
         }
     }
 }
 
+impl TaskConfig {
+    /// Filter applied to listed tasks, for the conditions not given
+    pub fn list_filter(&self) -> Result<Filter> {
+        let setting = |value: &str| Some(value.to_string()).filter(|value| !value.is_empty());
+        let status = setting(&self.list_status)
+            .map(|status| {
+                TaskStatus::from_str(&status)
+                    .map_err(|_| Error::Config(format!("Invalid task.list_status: {status}")))
+            })
+            .transpose()?;
+
+        Ok(Filter {
+            task_scope: setting(&self.list_scope),
+            task_type: setting(&self.list_type),
+            status,
+            ..Default::default()
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,6 +99,7 @@ mod tests {
         let config = TaskConfig {
             scopes: custom_scopes.clone(),
             types: custom_types.clone(),
+            ..Default::default()
         };
 
         // Check custom values were set correctly
@@ -84,4 +127,26 @@ mod tests {
         assert_eq!(deserialized.scopes, config.scopes);
         assert_eq!(deserialized.types, config.types);
     }
+
+    #[test]
+    fn test_list_filter() {
+        // No filter by default
+        assert!(TaskConfig::default().list_filter().unwrap().is_empty());
+
+        let config = TaskConfig {
+            list_scope: "rutd".to_string(),
+            list_status: "todo".to_string(),
+            ..Default::default()
+        };
+        let filter = config.list_filter().unwrap();
+        assert_eq!(filter.task_scope.as_deref(), Some("rutd"));
+        assert!(filter.task_type.is_none());
+        assert_eq!(filter.status, Some(TaskStatus::Todo));
+
+        let config = TaskConfig {
+            list_status: "unknown".to_string(),
+            ..Default::default()
+        };
+        assert!(config.list_filter().is_err());
+    }
 }
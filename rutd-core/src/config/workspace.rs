@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the workspace configured by the `path` and `git` sections
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Named task repository, with its own tasks, active task and git settings
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Workspace {
+    /// Root directory of the workspace, holding its tasks and active task
+    pub root_dir: PathBuf,
+    /// Git settings of the workspace, overriding the ones of the `git`
+    /// section
+    #[serde(default)]
+    pub git: toml::Table,
+}
+
+/// Workspace selection
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WorkspaceConfig {
+    /// Workspace used unless another one is given on the command line
+    ///
+    /// Falls back to the default workspace if empty.
+    pub current: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_serialization() {
+        let workspace: Workspace = toml::from_str(
+            r#"
+            root_dir = "/team/tasks"
+            git = { remote = "team" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(workspace.root_dir, PathBuf::from("/team/tasks"));
+        assert_eq!(workspace.git["remote"].as_str(), Some("team"));
+
+        // Git settings are optional
+        let workspace: Workspace = toml::from_str("root_dir = \"/tasks\"").unwrap();
+        assert!(workspace.git.is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::task::Task;
+use crate::task::{Resolution, Task, TaskConflict};
 
 /// Display interface for core functionality
 ///
@@ -24,6 +24,12 @@ pub trait Display {
     fn show_task_stats(&self, tasks: &[Task]);
     /// Display details for a specific task
     fn show_task_detail(&self, task: &Task);
+    /// Let the user resolve a task changed differently on both sides of a
+    /// sync
+    ///
+    /// Should return `None` if the user leaves the conflict to be resolved
+    /// manually.
+    fn resolve_conflict(&self, conflict: &TaskConflict) -> Result<Option<Resolution>>;
 }
 
 #[cfg(test)]
@@ -146,6 +152,10 @@ mod tests {
         fn show_task_detail(&self, task: &Task) {
             *self.task_detail_shown.borrow_mut() = Some(task.id.clone());
         }
+
+        fn resolve_conflict(&self, _conflict: &TaskConflict) -> Result<Option<Resolution>> {
+            Ok(None)
+        }
     }
 
     #[test]
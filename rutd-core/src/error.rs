@@ -0,0 +1,174 @@
+use std::path::PathBuf;
+
+use crate::task::TaskStatus;
+
+/// Result type used throughout rutd-core
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by rutd-core
+///
+/// Context added along the way (e.g. with [`anyhow::Context`]) is kept in the
+/// [`Error::Other`] variant, use [`Error::root`] to get the error that caused
+/// it.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// No task matches the given ID
+    #[error("No task found with ID starting with {0}")]
+    NotFound(String),
+    /// More than one task matches the given ID
+    #[error("Multiple tasks found with ID starting with {id}: {}", .candidates.join(", "))]
+    AmbiguousId {
+        /// The ID (prefix) that was looked up
+        id: String,
+        /// Full IDs of all matching tasks
+        candidates: Vec<String>,
+    },
+    /// No task is currently active
+    #[error("No active task found")]
+    NoActiveTask,
+    /// Task status cannot change as requested
+    #[error("{}", transition_message(*.from, *.to))]
+    InvalidTransition {
+        /// Current status of the task
+        from: TaskStatus,
+        /// Requested status of the task
+        to: TaskStatus,
+    },
+    /// Merging remote changes left conflicts to resolve
+    #[error(
+        "Merge conflicts detected in {}. Please resolve them manually. Then continue with 'sync --continue'",
+        .paths.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ")
+    )]
+    GitConflict {
+        /// Paths of the conflicting files, relative to the tasks directory
+        paths: Vec<PathBuf>,
+    },
+    /// Task file written by a newer version of rutd
+    #[error(
+        "Task file has schema version {version}, but only versions up to {supported} are supported. Please upgrade rutd."
+    )]
+    UnsupportedSchema {
+        /// Schema version of the task file
+        version: u32,
+        /// Latest schema version supported
+        supported: u32,
+    },
+    /// Invalid configuration
+    #[error("{0}")]
+    Config(String),
+    /// File system error
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Invalid TOML content
+    #[error(transparent)]
+    Parse(#[from] toml::de::Error),
+    /// Value that cannot be written as TOML
+    #[error(transparent)]
+    Serialize(#[from] toml::ser::Error),
+    /// Git error
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    /// Any other error, including errors with added context
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl Error {
+    /// Get the error that caused this one, looking through added context
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Other(e) => e.downcast_ref::<Self>().map_or(self, Self::root),
+            _ => self,
+        }
+    }
+
+    /// Whether the remote repository could not be reached
+    pub fn is_offline(&self) -> bool {
+        let git_error = match self.root() {
+            Self::Git(e) => Some(e),
+            Self::Other(e) => e.downcast_ref::<git2::Error>(),
+            _ => None,
+        };
+        git_error.is_some_and(|e| {
+            e.code() == git2::ErrorCode::Timeout
+                || matches!(
+                    e.class(),
+                    git2::ErrorClass::Net | git2::ErrorClass::Http | git2::ErrorClass::Ssh
+                )
+        })
+    }
+}
+
+/// Describe why a status transition is not allowed
+fn transition_message(from: TaskStatus, to: TaskStatus) -> String {
+    match to {
+        TaskStatus::Todo => format!("Task is {from} and cannot be started."),
+        _ if from == to => format!("Task is already {to}."),
+        TaskStatus::Done => format!("Task is {from} and cannot be marked done."),
+        TaskStatus::Aborted => format!("Task is {from} and cannot be aborted."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_root_error() {
+        let error: Error = Err::<(), _>(Error::NotFound("1234".to_string()))
+            .context("Failed to load task")
+            .unwrap_err()
+            .into();
+
+        // Context is kept in the message chain
+        assert_eq!(error.to_string(), "Failed to load task");
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to load task: No task found with ID starting with 1234"
+        );
+        // The original error is still reachable
+        assert!(matches!(error.root(), Error::NotFound(id) if id == "1234"));
+    }
+
+    #[test]
+    fn test_offline_error() {
+        let unreachable = git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Net,
+            "failed to resolve address",
+        );
+        let error: Error = Err::<(), _>(unreachable)
+            .context("Failed to fetch")
+            .unwrap_err()
+            .into();
+        assert!(error.is_offline());
+
+        assert!(!Error::NotFound("1234".to_string()).is_offline());
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let error = Error::AmbiguousId {
+            id: "12".to_string(),
+            candidates: vec!["1234".to_string(), "1256".to_string()],
+        };
+        assert_eq!(
+            error.to_string(),
+            "Multiple tasks found with ID starting with 12: 1234, 1256"
+        );
+
+        let error = Error::InvalidTransition {
+            from: TaskStatus::Done,
+            to: TaskStatus::Done,
+        };
+        assert_eq!(error.to_string(), "Task is already done.");
+
+        let error = Error::InvalidTransition {
+            from: TaskStatus::Aborted,
+            to: TaskStatus::Todo,
+        };
+        assert_eq!(error.to_string(), "Task is aborted and cannot be started.");
+    }
+}
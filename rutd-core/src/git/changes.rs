@@ -0,0 +1,308 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use git2::StatusOptions;
+
+use super::repo::GitRepo;
+use crate::Result;
+
+/// Change to a file in the repository
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileChange {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    /// Content before the change, `None` if the file does not exist yet
+    pub old: Option<String>,
+    /// Content after the change, `None` if the file is removed
+    pub new: Option<String>,
+}
+
+/// File changes recorded together by a single commit
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ChangeSet {
+    /// Message of the commit
+    pub message: String,
+    /// Changed files, in the order they are first changed
+    pub files: Vec<FileChange>,
+}
+
+impl ChangeSet {
+    /// Check if no file is changed
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Record writing a file in the repository at `root_dir`
+    ///
+    /// The path can be either absolute or relative to `root_dir`.
+    pub fn write(&mut self, root_dir: &Path, path: &Path, content: String) -> Result<()> {
+        self.record(root_dir, path, Some(content))
+    }
+
+    /// Record removing a file in the repository at `root_dir`
+    ///
+    /// The path can be either absolute or relative to `root_dir`.
+    pub fn remove(&mut self, root_dir: &Path, path: &Path) -> Result<()> {
+        self.record(root_dir, path, None)
+    }
+
+    /// Record the new content of a file, keeping its content before the first
+    /// change
+    fn record(&mut self, root_dir: &Path, path: &Path, new: Option<String>) -> Result<()> {
+        let path = path.strip_prefix(root_dir).unwrap_or(path).to_path_buf();
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+            file.new = new;
+            return Ok(());
+        }
+
+        let full_path = root_dir.join(&path);
+        let old = if full_path.is_file() {
+            Some(
+                fs::read_to_string(&full_path)
+                    .with_context(|| format!("Failed to read {}", full_path.display()))?,
+            )
+        } else {
+            None
+        };
+        self.files.push(FileChange { path, old, new });
+        Ok(())
+    }
+
+    /// Add the file changes of another change set
+    ///
+    /// Files changed by both keep their content before the first change.
+    pub fn extend(&mut self, other: &Self) {
+        for file in &other.files {
+            match self.files.iter_mut().find(|f| f.path == file.path) {
+                Some(existing) => existing.new.clone_from(&file.new),
+                None => self.files.push(file.clone()),
+            }
+        }
+    }
+
+    /// Write the changed files under `root_dir` without committing them
+    pub fn write_files(&self, root_dir: &Path) -> Result<()> {
+        for file in &self.files {
+            write_file(&root_dir.join(&file.path), file.new.as_deref())?;
+        }
+        Ok(())
+    }
+
+    /// Restore the changed files under `root_dir` to their content before
+    /// the changes
+    pub fn revert_files(&self, root_dir: &Path) -> Result<()> {
+        for file in self.files.iter().rev() {
+            write_file(&root_dir.join(&file.path), file.old.as_deref())?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a file, or remove it if there is no content
+fn write_file(path: &Path, content: Option<&str>) -> Result<()> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        None if path.exists() => {
+            fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+impl GitRepo {
+    /// Write the changed files to the working directory and commit them
+    pub fn apply(&self, changes: &ChangeSet) -> Result<()> {
+        let root_dir = self
+            .repo
+            .workdir()
+            .context("Tasks repository has no working directory")?;
+
+        changes.write_files(root_dir)?;
+        self.commit_changes(&changes.message)
+    }
+
+    /// Collect the changes in the working directory that are not committed
+    /// yet
+    ///
+    /// The returned change set has no message.
+    pub fn uncommitted_changes(&self) -> Result<ChangeSet> {
+        let root_dir = self
+            .repo
+            .workdir()
+            .context("Tasks repository has no working directory")?;
+        let head = self.head_id()?;
+
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+
+        let mut changes = ChangeSet::default();
+        for entry in self.repo.statuses(Some(&mut options))?.iter() {
+            let path = PathBuf::from(entry.path()?);
+            let old = self.read_file(head, &path)?;
+            let full_path = root_dir.join(&path);
+            let new = if full_path.is_file() {
+                Some(
+                    fs::read_to_string(&full_path)
+                        .with_context(|| format!("Failed to read {}", full_path.display()))?,
+                )
+            } else {
+                None
+            };
+            if old != new {
+                changes.files.push(FileChange { path, old, new });
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_change_set() {
+        let temp_dir = tempdir().unwrap();
+        let root_dir = temp_dir.path();
+        let git_repo = GitRepo::init(root_dir).unwrap();
+        fs::write(root_dir.join("old.toml"), "old").unwrap();
+        git_repo.commit_changes("Initial commit").unwrap();
+
+        let mut changes = ChangeSet {
+            message: "Change files".to_string(),
+            ..Default::default()
+        };
+        changes
+            .write(root_dir, Path::new("new.toml"), "first".to_string())
+            .unwrap();
+        changes
+            .write(root_dir, &root_dir.join("new.toml"), "second".to_string())
+            .unwrap();
+        changes
+            .write(root_dir, Path::new("dir/moved.toml"), "old".to_string())
+            .unwrap();
+        changes
+            .remove(root_dir, &root_dir.join("old.toml"))
+            .unwrap();
+
+        // Nothing is written until the changes are applied
+        assert!(!root_dir.join("new.toml").exists());
+        assert_eq!(
+            changes.files,
+            vec![
+                FileChange {
+                    path: PathBuf::from("new.toml"),
+                    old: None,
+                    new: Some("second".to_string()),
+                },
+                FileChange {
+                    path: PathBuf::from("dir/moved.toml"),
+                    old: None,
+                    new: Some("old".to_string()),
+                },
+                FileChange {
+                    path: PathBuf::from("old.toml"),
+                    old: Some("old".to_string()),
+                    new: None,
+                },
+            ]
+        );
+
+        git_repo.apply(&changes).unwrap();
+        assert_eq!(
+            fs::read_to_string(root_dir.join("new.toml")).unwrap(),
+            "second"
+        );
+        assert!(root_dir.join("dir/moved.toml").exists());
+        assert!(!root_dir.join("old.toml").exists());
+        assert_eq!(
+            git_repo.recent_commits(1).unwrap()[0].summary(),
+            "Change files"
+        );
+    }
+
+    #[test]
+    fn test_uncommitted_changes() {
+        let temp_dir = tempdir().unwrap();
+        let root_dir = temp_dir.path();
+        let git_repo = GitRepo::init(root_dir).unwrap();
+        fs::write(root_dir.join("old.toml"), "old").unwrap();
+        fs::write(root_dir.join("kept.toml"), "kept").unwrap();
+        git_repo.commit_changes("Initial commit").unwrap();
+        assert!(git_repo.uncommitted_changes().unwrap().is_empty());
+
+        fs::write(root_dir.join("old.toml"), "changed").unwrap();
+        fs::create_dir(root_dir.join("dir")).unwrap();
+        fs::write(root_dir.join("dir/new.toml"), "new").unwrap();
+        fs::remove_file(root_dir.join("kept.toml")).unwrap();
+
+        let mut files = git_repo.uncommitted_changes().unwrap().files;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            files,
+            vec![
+                FileChange {
+                    path: PathBuf::from("dir/new.toml"),
+                    old: None,
+                    new: Some("new".to_string()),
+                },
+                FileChange {
+                    path: PathBuf::from("kept.toml"),
+                    old: Some("kept".to_string()),
+                    new: None,
+                },
+                FileChange {
+                    path: PathBuf::from("old.toml"),
+                    old: Some("old".to_string()),
+                    new: Some("changed".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extend_and_revert_files() {
+        let temp_dir = tempdir().unwrap();
+        let root_dir = temp_dir.path();
+        fs::write(root_dir.join("task.toml"), "old").unwrap();
+
+        let mut first = ChangeSet::default();
+        first
+            .write(root_dir, Path::new("task.toml"), "first".to_string())
+            .unwrap();
+        first.write_files(root_dir).unwrap();
+        let mut second = ChangeSet::default();
+        second
+            .write(root_dir, Path::new("task.toml"), "second".to_string())
+            .unwrap();
+        second
+            .write(root_dir, Path::new("new.toml"), "new".to_string())
+            .unwrap();
+        second.write_files(root_dir).unwrap();
+
+        first.extend(&second);
+        assert_eq!(first.files.len(), 2);
+        assert_eq!(first.files[0].old.as_deref(), Some("old"));
+        assert_eq!(first.files[0].new.as_deref(), Some("second"));
+
+        first.revert_files(root_dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(root_dir.join("task.toml")).unwrap(),
+            "old"
+        );
+        assert!(!root_dir.join("new.toml").exists());
+    }
+}
@@ -0,0 +1,310 @@
+use std::{env, path::PathBuf, process::Command};
+
+use anyhow::Context;
+use git2::{Cred, CredentialType};
+
+use crate::{Result, config::GitConfig};
+
+/// SSH keys tried when no key is configured, relative to the home directory
+const DEFAULT_SSH_KEYS: [&str; 5] = [
+    ".ssh/id_rsa",
+    ".ssh/id_ed25519",
+    ".ssh/id_ecdsa",
+    ".ssh/id_dsa",
+    ".ssh/github_rsa",
+];
+
+/// Where credentials for the remote repository come from
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Source {
+    /// SSH private key file
+    SshKey(PathBuf),
+    /// Keys loaded in the SSH agent
+    SshAgent,
+    /// Password or token from the configuration, an environment variable or a
+    /// command
+    Password,
+    /// Git credential helpers
+    Helper,
+    /// Default credentials of the system, for NTLM or Kerberos
+    Default,
+}
+
+/// Provide credentials when the remote repository asks for them
+///
+/// Each source is tried in turn: when the remote rejects credentials, it asks
+/// again and the next source is used, until none is left.
+pub struct CredentialProvider {
+    git_config: GitConfig,
+    /// Git configuration holding the credential helpers
+    repo_config: Option<git2::Config>,
+    /// Number of sources tried so far
+    attempts: usize,
+}
+
+impl CredentialProvider {
+    /// Create a provider from the settings
+    ///
+    /// Credential helpers are read from `repo_config`, or from the global git
+    /// configuration if `None`.
+    pub fn new(git_config: &GitConfig, repo_config: Option<git2::Config>) -> Self {
+        Self {
+            git_config: git_config.clone(),
+            repo_config,
+            attempts: 0,
+        }
+    }
+
+    /// Credential callback for [`git2::RemoteCallbacks::credentials`]
+    pub fn credential(
+        &mut self,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed_types: CredentialType,
+    ) -> Result<Cred, git2::Error> {
+        log::debug!("Attempting authentication for URL: {url}");
+        log::debug!("Allowed credential types: {allowed_types:?}");
+
+        // SSH asks for the username first
+        if allowed_types.contains(CredentialType::USERNAME) {
+            return Cred::username(username_from_url.unwrap_or("git"));
+        }
+
+        let sources = self.sources(allowed_types);
+        while let Some(source) = sources.get(self.attempts) {
+            self.attempts += 1;
+            log::debug!("Trying credentials from {source:?}");
+            match self.try_source(source, url, username_from_url) {
+                Ok(cred) => return Ok(cred),
+                Err(e) => log::debug!("Failed to use credentials from {source:?}: {e}"),
+            }
+        }
+
+        Err(git2::Error::from_str(&format!(
+            "Authentication to {url} failed, set git.ssh_key, git.password_env or git.password_command, or configure a git credential helper"
+        )))
+    }
+
+    /// List the sources of credentials of the allowed types, in order
+    fn sources(&self, allowed_types: CredentialType) -> Vec<Source> {
+        let mut sources = Vec::new();
+        if allowed_types.contains(CredentialType::SSH_KEY) {
+            if self.git_config.ssh_key.is_empty() {
+                let home = env::var("HOME").map(PathBuf::from).unwrap_or_default();
+                sources.extend(
+                    DEFAULT_SSH_KEYS
+                        .iter()
+                        .map(|key| home.join(key))
+                        .filter(|path| path.exists())
+                        .map(Source::SshKey),
+                );
+                sources.push(Source::SshAgent);
+            } else {
+                let key = shellexpand::tilde(&self.git_config.ssh_key);
+                sources.push(Source::SshKey(PathBuf::from(key.as_ref())));
+            }
+        }
+        if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            sources.push(Source::Password);
+            sources.push(Source::Helper);
+        }
+        if allowed_types.contains(CredentialType::DEFAULT) {
+            sources.push(Source::Default);
+        }
+        sources
+    }
+
+    /// Get credentials from a source
+    fn try_source(
+        &self,
+        source: &Source,
+        url: &str,
+        username_from_url: Option<&str>,
+    ) -> Result<Cred> {
+        let ssh_username = username_from_url.unwrap_or("git");
+        let cred = match source {
+            Source::SshKey(path) => {
+                Cred::ssh_key(ssh_username, None, path, self.ssh_passphrase()?.as_deref())?
+            }
+            Source::SshAgent => Cred::ssh_key_from_agent(ssh_username)?,
+            Source::Password => {
+                let password = self
+                    .password()?
+                    .context("No password or token configured")?;
+                let username = Some(self.git_config.username.as_str())
+                    .filter(|username| !username.is_empty())
+                    .or(username_from_url)
+                    .unwrap_or("git");
+                Cred::userpass_plaintext(username, &password)?
+            }
+            Source::Helper => match &self.repo_config {
+                Some(config) => Cred::credential_helper(config, url, username_from_url)?,
+                None => {
+                    Cred::credential_helper(&git2::Config::open_default()?, url, username_from_url)?
+                }
+            },
+            Source::Default => Cred::default()?,
+        };
+        Ok(cred)
+    }
+
+    /// Get the passphrase of the SSH key from the environment variable named
+    /// in `git.ssh_passphrase_env`, if any
+    fn ssh_passphrase(&self) -> Result<Option<String>> {
+        let name = &self.git_config.ssh_passphrase_env;
+        if name.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(env::var(name).with_context(|| {
+            format!("Environment variable {name} holding the SSH key passphrase is not set")
+        })?))
+    }
+
+    /// Get the password or token
+    ///
+    /// The environment variable named in `git.password_env` comes first, then
+    /// the output of `git.password_command`, then `git.password`.
+    fn password(&self) -> Result<Option<String>> {
+        let git_config = &self.git_config;
+        if !git_config.password_env.is_empty() {
+            let name = &git_config.password_env;
+            return Ok(Some(env::var(name).with_context(|| {
+                format!("Environment variable {name} holding the password is not set")
+            })?));
+        }
+        if !git_config.password_command.is_empty() {
+            return run_password_command(&git_config.password_command).map(Some);
+        }
+        Ok(Some(git_config.password.clone()).filter(|password| !password.is_empty()))
+    }
+}
+
+/// Run a shell command printing the password or token on its first line
+fn run_password_command(command: &str) -> Result<String> {
+    log::debug!("Running password command");
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let output = Command::new(shell)
+        .args([flag, command])
+        .output()
+        .context("Failed to run the password command")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Password command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let stdout = String::from_utf8(output.stdout).context("Invalid password")?;
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_credential_sources() {
+        let temp_dir = tempdir().unwrap();
+        let key = temp_dir.path().join("key");
+        fs::write(&key, "").unwrap();
+
+        // A configured key replaces the default keys and the agent
+        let git_config = GitConfig {
+            ssh_key: key.display().to_string(),
+            ..Default::default()
+        };
+        let provider = CredentialProvider::new(&git_config, None);
+        assert_eq!(
+            provider.sources(CredentialType::SSH_KEY),
+            vec![Source::SshKey(key)]
+        );
+
+        let provider = CredentialProvider::new(&GitConfig::default(), None);
+        assert_eq!(
+            provider.sources(CredentialType::SSH_KEY).last(),
+            Some(&Source::SshAgent)
+        );
+        assert_eq!(
+            provider.sources(CredentialType::USER_PASS_PLAINTEXT),
+            vec![Source::Password, Source::Helper]
+        );
+    }
+
+    #[test]
+    fn test_password_sources() {
+        // Nothing configured
+        let provider = CredentialProvider::new(&GitConfig::default(), None);
+        assert_eq!(provider.password().unwrap(), None);
+
+        // Plaintext password
+        let git_config = GitConfig {
+            password: "plaintext".to_string(),
+            ..Default::default()
+        };
+        let provider = CredentialProvider::new(&git_config, None);
+        assert_eq!(provider.password().unwrap().as_deref(), Some("plaintext"));
+
+        // A command comes first
+        let git_config = GitConfig {
+            password: "plaintext".to_string(),
+            password_command: "echo from-command".to_string(),
+            ..Default::default()
+        };
+        let provider = CredentialProvider::new(&git_config, None);
+        assert_eq!(
+            provider.password().unwrap().as_deref(),
+            Some("from-command")
+        );
+
+        // A missing environment variable is an error
+        let git_config = GitConfig {
+            password_env: "RUTD_TEST_MISSING_PASSWORD".to_string(),
+            ..git_config
+        };
+        let provider = CredentialProvider::new(&git_config, None);
+        assert!(provider.password().is_err());
+
+        let git_config = GitConfig {
+            password_command: "exit 1".to_string(),
+            ..Default::default()
+        };
+        let provider = CredentialProvider::new(&git_config, None);
+        assert!(provider.password().is_err());
+    }
+
+    #[test]
+    fn test_credential_attempts() {
+        let git_config = GitConfig {
+            username: "test-user".to_string(),
+            password: "test-password".to_string(),
+            ..Default::default()
+        };
+        let temp_dir = tempdir().unwrap();
+        let repo_config = git2::Config::open(&temp_dir.path().join("config")).unwrap();
+        let mut provider = CredentialProvider::new(&git_config, Some(repo_config));
+        let url = "https://example.com/tasks.git";
+
+        // The password is used first
+        assert!(
+            provider
+                .credential(url, None, CredentialType::USER_PASS_PLAINTEXT)
+                .is_ok()
+        );
+
+        // Then the credential helpers, none here, and nothing is left to try
+        assert!(
+            provider
+                .credential(url, None, CredentialType::USER_PASS_PLAINTEXT)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use git2::{Commit, ObjectType, Oid, ResetType, Sort, Tree, build::CheckoutBuilder};
+
+use super::repo::{COMMITTER_EMAIL, GitRepo};
+use crate::Result;
+
+/// Action of the commits upgrading task files, which are not changes made by
+/// users
+pub const MIGRATE_ACTION: &str = "migrate";
+
+/// Information about a commit in the tasks repository
+#[derive(Clone, Debug)]
+pub struct CommitInfo {
+    /// Commit ID
+    pub id: Oid,
+    /// First parent of the commit, `None` for the initial commit
+    pub parent: Option<Oid>,
+    /// Full commit message
+    pub message: String,
+    /// Name of the author of the commit
+    pub author: String,
+    /// Commit time
+    pub time: DateTime<Local>,
+    /// Whether the commit has been pushed to a remote-tracking branch
+    pub pushed: bool,
+}
+
+impl CommitInfo {
+    /// First line of the commit message
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+impl GitRepo {
+    /// Get the commit HEAD points to, if any
+    pub fn head_id(&self) -> Result<Option<Oid>> {
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_commit()?.id())),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Collect information about a commit
+    pub(crate) fn commit_info(&self, commit: &Commit) -> Result<CommitInfo> {
+        let time = DateTime::from_timestamp(commit.time().seconds(), 0)
+            .context("Invalid commit time")?
+            .with_timezone(&Local);
+
+        Ok(CommitInfo {
+            id: commit.id(),
+            parent: commit.parent_ids().next(),
+            message: commit.message().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time,
+            pushed: self.is_pushed(commit.id())?,
+        })
+    }
+
+    /// Check if a commit is reachable from any remote-tracking branch
+    fn is_pushed(&self, id: Oid) -> Result<bool> {
+        for reference in self.repo.references_glob("refs/remotes/*")? {
+            let Some(tip) = reference?.target() else {
+                continue;
+            };
+            if tip == id || self.repo.graph_descendant_of(tip, id)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Get the most recent commits made by rutd on the current branch
+    ///
+    /// Only the first parent of each commit is followed. Migration commits
+    /// are not counted as changes, but are returned along with the changes
+    /// made after them. Fails if fewer than `count` commits can be undone,
+    /// i.e. when a commit not made by rutd, a merge commit or the start of the
+    /// history is reached.
+    pub fn recent_commits(&self, count: usize) -> Result<Vec<CommitInfo>> {
+        let Some(head) = self.head_id()? else {
+            return Err(anyhow::anyhow!("No commits found in the tasks repository").into());
+        };
+
+        let mut commits = Vec::with_capacity(count);
+        let mut changes = 0;
+        let mut next = Some(self.repo.find_commit(head)?);
+        while changes < count {
+            let Some(commit) = next else {
+                return Err(anyhow::anyhow!("Only {changes} changes can be undone").into());
+            };
+            if commit.committer().email().ok() != Some(COMMITTER_EMAIL) {
+                return Err(anyhow::anyhow!("Commit {} was not made by rutd", commit.id()).into());
+            }
+            if commit.parent_count() > 1 {
+                return Err(anyhow::anyhow!("Commit {} is a merge commit", commit.id()).into());
+            }
+            let info = self.commit_info(&commit)?;
+            let is_migration = GitRepo::parse_commit_message(&info.message)
+                .is_some_and(|message| message.action == MIGRATE_ACTION);
+            if !is_migration {
+                changes += 1;
+            }
+            commits.push(info);
+            next = commit.parent(0).ok();
+        }
+
+        Ok(commits)
+    }
+
+    /// Get all non-merge commits reachable from HEAD, oldest first
+    pub fn history(&self) -> Result<Vec<CommitInfo>> {
+        let Some(head) = self.head_id()? else {
+            return Ok(Vec::new());
+        };
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)?;
+        revwalk.push(head)?;
+
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            // Changes in merge commits are already recorded in their parents
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            commits.push(self.commit_info(&commit)?);
+        }
+
+        Ok(commits)
+    }
+
+    /// Get the first parent of a commit
+    pub fn parent_id(&self, id: Oid) -> Result<Option<Oid>> {
+        Ok(self.repo.find_commit(id)?.parent_ids().next())
+    }
+
+    /// Get the files of a commit, `None` before the initial commit
+    fn commit_tree(&self, id: Option<Oid>) -> Result<Option<Tree<'_>>> {
+        id.map(|id| Ok(self.repo.find_commit(id)?.tree()?))
+            .transpose()
+    }
+
+    /// List files that differ between two commits
+    ///
+    /// A missing commit stands for the empty state before the initial commit.
+    pub fn changed_files(&self, from: Option<Oid>, to: Option<Oid>) -> Result<Vec<PathBuf>> {
+        let from_tree = self.commit_tree(from)?;
+        let to_tree = self.commit_tree(to)?;
+        let diff = self
+            .repo
+            .diff_tree_to_tree(from_tree.as_ref(), to_tree.as_ref(), None)?;
+
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())
+                    .map(Path::to_path_buf)
+            })
+            .collect())
+    }
+
+    /// Read a file as it was at the given commit
+    ///
+    /// Returns `None` if the file does not exist at that commit, or if there
+    /// is no commit.
+    pub fn read_file(&self, id: Option<Oid>, path: &Path) -> Result<Option<String>> {
+        let Some(tree) = self.commit_tree(id)? else {
+            return Ok(None);
+        };
+        let Ok(entry) = tree.get_path(path) else {
+            return Ok(None);
+        };
+        if entry.kind() != Some(ObjectType::Blob) {
+            return Ok(None);
+        }
+        let blob = self.repo.find_blob(entry.id())?;
+        let content = String::from_utf8(blob.content().to_vec())
+            .with_context(|| format!("Invalid UTF-8 in {}", path.display()))?;
+        Ok(Some(content))
+    }
+
+    /// Move the current branch back to the given commit, discarding all later
+    /// commits and changes
+    pub fn reset_to(&self, id: Oid) -> Result<()> {
+        let commit = self.repo.find_commit(id)?;
+        self.repo.reset(commit.as_object(), ResetType::Hard, None)?;
+
+        log::debug!("Reset to commit: {id}");
+        Ok(())
+    }
+
+    /// Restore the files to their state at the given commit and record it as
+    /// a new commit on top of the current branch
+    ///
+    /// Without a commit, all files are removed as before the initial commit.
+    pub fn revert_to(&self, id: Option<Oid>, message: &str) -> Result<()> {
+        let tree = match self.commit_tree(id)? {
+            Some(tree) => tree,
+            None => self.repo.find_tree(self.repo.treebuilder(None)?.write()?)?,
+        };
+        self.repo.checkout_tree(
+            tree.as_object(),
+            Some(CheckoutBuilder::new().force().remove_untracked(true)),
+        )?;
+        self.commit_changes(message)?;
+
+        log::debug!("Reverted to the state of commit: {id:?}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_recent_commits_and_reset() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = temp_dir.path();
+        let git_repo = GitRepo::init(repo_path).unwrap();
+
+        for i in 1..=3 {
+            fs::write(repo_path.join(format!("{i}.toml")), format!("id = \"{i}\"")).unwrap();
+            git_repo.commit_changes(&format!("Commit {i}")).unwrap();
+        }
+
+        // Only the commits in the history can be undone, including the initial
+        // one
+        assert!(git_repo.recent_commits(4).is_err());
+        assert!(git_repo.recent_commits(3).unwrap()[2].parent.is_none());
+
+        let commits = git_repo.recent_commits(2).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].summary(), "Commit 3");
+        assert_eq!(commits[1].summary(), "Commit 2");
+        assert!(!commits[0].pushed);
+
+        let target = git_repo.parent_id(commits[1].id).unwrap().unwrap();
+        let changed = git_repo
+            .changed_files(Some(target), Some(commits[0].id))
+            .unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(
+            git_repo
+                .read_file(Some(commits[0].id), Path::new("3.toml"))
+                .unwrap()
+                .as_deref(),
+            Some("id = \"3\"")
+        );
+        assert!(
+            git_repo
+                .read_file(Some(target), Path::new("3.toml"))
+                .unwrap()
+                .is_none()
+        );
+
+        let history = git_repo.history().unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].summary(), "Commit 1");
+        assert!(history[0].parent.is_none());
+        assert_eq!(history[2].id, commits[0].id);
+
+        git_repo.reset_to(target).unwrap();
+        assert_eq!(git_repo.head_id().unwrap(), Some(target));
+        assert!(repo_path.join("1.toml").exists());
+        assert!(!repo_path.join("2.toml").exists());
+        assert!(!repo_path.join("3.toml").exists());
+    }
+
+    #[test]
+    fn test_recent_commits_skip_migrations() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = temp_dir.path();
+        let git_repo = GitRepo::init(repo_path).unwrap();
+
+        let messages = [
+            "Initial commit".to_string(),
+            GitRepo::generate_commit_message("create", None, None, "Add task", "1"),
+            GitRepo::generate_commit_message("update", None, None, "Update task", "1"),
+            GitRepo::generate_commit_message(MIGRATE_ACTION, None, None, "Migrate tasks", "1"),
+        ];
+        for (i, message) in messages.iter().enumerate() {
+            fs::write(repo_path.join("1.toml"), format!("version = {i}")).unwrap();
+            git_repo.commit_changes(message).unwrap();
+        }
+
+        // The migration is undone along with the last change, which is counted
+        let commits = git_repo.recent_commits(1).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].message, messages[3]);
+        assert_eq!(commits[1].message, messages[2]);
+
+        let commits = git_repo.recent_commits(2).unwrap();
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[2].message, messages[1]);
+    }
+
+    #[test]
+    fn test_revert_to() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = temp_dir.path();
+        let git_repo = GitRepo::init(repo_path).unwrap();
+
+        fs::write(repo_path.join("1.toml"), "id = \"1\"").unwrap();
+        git_repo.commit_changes("Commit 1").unwrap();
+        let target = git_repo.head_id().unwrap().unwrap();
+        fs::write(repo_path.join("2.toml"), "id = \"2\"").unwrap();
+        git_repo.commit_changes("Commit 2").unwrap();
+
+        git_repo.revert_to(Some(target), "Revert").unwrap();
+
+        // A new commit is created with the old state
+        let commits = git_repo.recent_commits(1).unwrap();
+        assert_eq!(commits[0].summary(), "Revert");
+        assert!(
+            git_repo
+                .changed_files(Some(target), Some(commits[0].id))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!repo_path.join("2.toml").exists());
+
+        // Without a commit, every file is removed
+        git_repo.revert_to(None, "Revert all").unwrap();
+        let head = git_repo.head_id().unwrap();
+        assert!(git_repo.changed_files(None, head).unwrap().is_empty());
+        assert!(!repo_path.join("1.toml").exists());
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use git2::Signature;
+
+use crate::{
+    Result,
+    config::{CommitSigning, GitConfig},
+};
+
+/// Author and signing settings applied to commits made by rutd
+///
+/// The committer is always rutd, so that commits made by rutd can be told
+/// apart from the others, while the author records who made the change.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommitIdentity {
+    /// Name and email of the author, `None` to author commits as rutd
+    pub author: Option<(String, String)>,
+    /// How commits are signed
+    pub signing: CommitSigning,
+    /// Key used to sign commits, `None` to use the default key
+    pub signing_key: Option<String>,
+}
+
+impl CommitIdentity {
+    /// Resolve the identity from the configuration
+    ///
+    /// Empty settings fall back to the git configuration, if any.
+    pub fn resolve(git_config: &GitConfig, repo_config: Option<&git2::Config>) -> Self {
+        let setting = |value: &str, key: &str| {
+            Some(value.to_string())
+                .filter(|value| !value.is_empty())
+                .or_else(|| repo_config.and_then(|config| config.get_string(key).ok()))
+                .filter(|value| !value.is_empty())
+        };
+
+        let name = setting(&git_config.author_name, "user.name");
+        let email = setting(&git_config.author_email, "user.email");
+        Self {
+            author: name.zip(email),
+            signing: git_config.signing,
+            signing_key: setting(&git_config.signing_key, "user.signingkey"),
+        }
+    }
+
+    /// Get the author signature, `None` to author commits as rutd
+    pub fn author(&self) -> Result<Option<Signature<'static>>> {
+        Ok(self
+            .author
+            .as_ref()
+            .map(|(name, email)| Signature::now(name, email))
+            .transpose()?)
+    }
+
+    /// Sign the content of a commit, `None` if commits are not signed
+    pub fn sign(&self, content: &str) -> Result<Option<String>> {
+        let mut command = match self.signing {
+            CommitSigning::None => return Ok(None),
+            CommitSigning::Gpg => {
+                let mut command = Command::new("gpg");
+                command.args(["--detach-sign", "--armor"]);
+                if let Some(key) = &self.signing_key {
+                    command.args(["--local-user", key]);
+                }
+                command
+            }
+            CommitSigning::Ssh => {
+                let key = self
+                    .signing_key
+                    .as_deref()
+                    .context("A signing key is required to sign commits with SSH")?;
+                let mut command = Command::new("ssh-keygen");
+                command
+                    .args(["-Y", "sign", "-n", "git", "-f"])
+                    .arg(shellexpand::tilde(key).as_ref());
+                command
+            }
+        };
+
+        let program = command.get_program().to_string_lossy().into_owned();
+        log::debug!("Signing commit with {program}");
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run {program} to sign the commit"))?;
+        child
+            .stdin
+            .take()
+            .context("Failed to open the standard input of the signing program")?
+            .write_all(content.as_bytes())?;
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "Failed to sign the commit with {program}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        Ok(Some(
+            String::from_utf8(output.stdout).context("Invalid signature")?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_identity() {
+        let temp_dir = tempdir().unwrap();
+        let mut repo_config = git2::Config::open(&temp_dir.path().join("config")).unwrap();
+        repo_config.set_str("user.name", "Git User").unwrap();
+        repo_config
+            .set_str("user.email", "git@example.com")
+            .unwrap();
+
+        // Nothing configured
+        let identity = CommitIdentity::resolve(&GitConfig::default(), None);
+        assert_eq!(identity, CommitIdentity::default());
+        assert!(identity.author().unwrap().is_none());
+        assert!(identity.sign("content").unwrap().is_none());
+
+        // Fall back to the git configuration
+        let identity = CommitIdentity::resolve(&GitConfig::default(), Some(&repo_config));
+        assert_eq!(
+            identity.author,
+            Some(("Git User".to_string(), "git@example.com".to_string()))
+        );
+
+        // Settings take precedence
+        let git_config = GitConfig {
+            author_name: "Alice".to_string(),
+            signing: CommitSigning::Ssh,
+            ..Default::default()
+        };
+        let identity = CommitIdentity::resolve(&git_config, Some(&repo_config));
+        assert_eq!(
+            identity.author,
+            Some(("Alice".to_string(), "git@example.com".to_string()))
+        );
+
+        // SSH signing needs a key
+        assert!(identity.sign("content").is_err());
+    }
+}
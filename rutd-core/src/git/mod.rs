@@ -1,3 +1,8 @@
+pub mod changes;
+pub mod credentials;
+pub mod history;
+pub mod identity;
+pub mod remote;
 pub mod repo;
 
 use std::fmt;
@@ -26,6 +31,13 @@ pub enum MergeStrategy {
         message = "Prefer remote version"
     )]
     Remote,
+    /// Resolve conflicts interactively
+    #[strum(
+        serialize = "i",
+        serialize = "interactive",
+        message = "Resolve conflicts interactively"
+    )]
+    Interactive,
 }
 
 impl fmt::Display for MergeStrategy {
@@ -34,6 +46,7 @@ impl fmt::Display for MergeStrategy {
             Self::None => write!(f, "None"),
             Self::Local => write!(f, "Local"),
             Self::Remote => write!(f, "Remote"),
+            Self::Interactive => write!(f, "Interactive"),
         }
     }
 }
@@ -53,6 +66,7 @@ mod tests {
         assert_eq!(MergeStrategy::None.to_string(), "None");
         assert_eq!(MergeStrategy::Local.to_string(), "Local");
         assert_eq!(MergeStrategy::Remote.to_string(), "Remote");
+        assert_eq!(MergeStrategy::Interactive.to_string(), "Interactive");
     }
 
     #[test]
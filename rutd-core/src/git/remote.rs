@@ -0,0 +1,365 @@
+use std::{fs, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use git2::{ErrorCode, RepositoryState, Sort};
+
+use super::{history::CommitInfo, repo::GitRepo};
+use crate::{
+    Result,
+    config::{GitConfig, SyncMode},
+};
+
+/// Name of the remote used when none is configured
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// Remote of the tasks repository
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoteInfo {
+    /// Name of the remote
+    pub name: String,
+    /// URL of the remote, `None` if it is not valid UTF-8
+    pub url: Option<String>,
+}
+
+/// Remote branch the current branch is synced with
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncTarget {
+    /// Name of the remote
+    pub remote: String,
+    /// Name of the local branch
+    pub local_branch: String,
+    /// Name of the branch on the remote
+    pub remote_branch: String,
+}
+
+/// State of the local branch compared to the remote branch it is synced with
+///
+/// Computed from the remote-tracking branch as of the last fetch, without
+/// contacting the remote.
+#[derive(Clone, Debug)]
+pub struct SyncStatus {
+    /// Remote branch the tasks are synced with
+    pub target: SyncTarget,
+    /// Whether the remote branch has been fetched at least once
+    pub tracked: bool,
+    /// Number of local commits not pushed yet
+    pub ahead: usize,
+    /// Number of remote commits not merged yet
+    pub behind: usize,
+    /// Number of changed files not committed yet
+    pub uncommitted: usize,
+    /// When the remote was last fetched, `None` if never
+    pub last_fetch: Option<DateTime<Local>>,
+    /// Whether a sync is waiting for conflicts to be resolved
+    pub in_progress: bool,
+}
+
+impl SyncTarget {
+    /// Reference of the local branch
+    pub fn local_ref(&self) -> String {
+        format!("refs/heads/{}", self.local_branch)
+    }
+
+    /// Remote-tracking reference of the remote branch
+    pub fn tracking_ref(&self) -> String {
+        format!("refs/remotes/{}/{}", self.remote, self.remote_branch)
+    }
+
+    /// Message of the commit merging the remote branch
+    pub fn merge_message(&self) -> String {
+        format!(
+            "Merge remote-tracking branch '{}' into '{}'",
+            self.tracking_ref(),
+            self.local_branch
+        )
+    }
+
+    /// Describe how diverged local and remote branches are combined
+    pub fn sync_message(&self, mode: SyncMode) -> String {
+        match mode {
+            SyncMode::Merge => self.merge_message(),
+            SyncMode::Rebase => format!(
+                "Rebase '{}' onto remote-tracking branch '{}'",
+                self.local_branch,
+                self.tracking_ref()
+            ),
+        }
+    }
+}
+
+impl GitRepo {
+    /// List the remotes of the repository
+    pub fn remotes(&self) -> Result<Vec<RemoteInfo>> {
+        let mut remotes = Vec::new();
+        for name in self.repo.remotes()?.iter() {
+            let Some(name) = name? else {
+                continue;
+            };
+            let remote = self.repo.find_remote(name)?;
+            remotes.push(RemoteInfo {
+                name: name.to_string(),
+                url: remote.url().ok().map(String::from),
+            });
+        }
+        Ok(remotes)
+    }
+
+    /// Add a remote to the repository
+    pub fn add_remote(&self, name: &str, url: &str) -> Result<()> {
+        self.repo
+            .remote(name, url)
+            .with_context(|| format!("Failed to add remote '{name}'"))?;
+        log::debug!("Added remote '{name}': {url}");
+        Ok(())
+    }
+
+    /// Change the URL of a remote
+    pub fn set_remote_url(&self, name: &str, url: &str) -> Result<()> {
+        self.repo
+            .find_remote(name)
+            .with_context(|| format!("No remote named '{name}' found"))?;
+        self.repo.remote_set_url(name, url)?;
+        log::debug!("Set URL of remote '{name}': {url}");
+        Ok(())
+    }
+
+    /// Get the name of the current branch, `None` if HEAD is detached
+    ///
+    /// While a sync is rebasing, this is the branch being rebased.
+    fn current_branch(&self) -> Result<Option<String>> {
+        let head = self.repo.find_reference("HEAD")?;
+        let branch = match head.symbolic_target()? {
+            Some(target) => Some(target.to_string()),
+            None if self.repo.state() == RepositoryState::RebaseMerge => self
+                .repo
+                .open_rebase(None)?
+                .orig_head_name()
+                .context("Invalid UTF-8 in branch name")?
+                .map(String::from),
+            None => None,
+        };
+        Ok(branch
+            .as_deref()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .map(String::from))
+    }
+
+    /// Get the upstream remote and branch configured for a local branch
+    fn upstream(&self, branch: &str) -> Result<(Option<String>, Option<String>)> {
+        let local_ref = format!("refs/heads/{branch}");
+        let optional = |result: std::result::Result<git2::Buf, git2::Error>| match result {
+            Ok(buf) => Ok(buf.as_str().ok().map(String::from)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        let remote = optional(self.repo.branch_upstream_remote(&local_ref))?;
+        let merge = optional(self.repo.branch_upstream_merge(&local_ref))?;
+        Ok((
+            remote,
+            merge.and_then(|merge| merge.strip_prefix("refs/heads/").map(String::from)),
+        ))
+    }
+
+    /// Get the remote used by default
+    ///
+    /// The remote configured in `git.remote` comes first, then the upstream
+    /// remote of the current branch, then `origin`.
+    pub fn default_remote(&self, git_config: &GitConfig) -> Result<String> {
+        if !git_config.remote.is_empty() {
+            return Ok(git_config.remote.clone());
+        }
+        let upstream = match self.current_branch()? {
+            Some(branch) => self.upstream(&branch)?.0,
+            None => None,
+        };
+        Ok(upstream.unwrap_or_else(|| DEFAULT_REMOTE.to_string()))
+    }
+
+    /// Give up on remote repositories not responding within the timeout
+    ///
+    /// Applies to connecting and to every read or write of the following
+    /// network operations of the process.
+    pub fn set_network_timeout(timeout: Duration) -> Result<()> {
+        let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+        // SAFETY: the options are global to libgit2, and rutd never runs
+        // network operations on several threads
+        unsafe {
+            git2::opts::set_server_connect_timeout_in_milliseconds(millis)?;
+            git2::opts::set_server_timeout_in_milliseconds(millis)?;
+        }
+        Ok(())
+    }
+
+    /// Compare the local branch with the remote branch it is synced with
+    ///
+    /// Returns `None` if no remote is configured.
+    pub fn sync_status(&self, git_config: &GitConfig) -> Result<Option<SyncStatus>> {
+        if self.repo.remotes()?.is_empty() {
+            return Ok(None);
+        }
+
+        let target = self.sync_target(git_config)?;
+        let local = self.repo.refname_to_id(&target.local_ref()).ok();
+        let remote = self.repo.refname_to_id(&target.tracking_ref()).ok();
+        let (ahead, behind) = match (local, remote) {
+            (Some(local), Some(remote)) => self.repo.graph_ahead_behind(local, remote)?,
+            (Some(_), None) => (self.unpushed_commits(git_config)?.len(), 0),
+            (None, _) => (0, 0),
+        };
+
+        // The modification time of FETCH_HEAD tells when the remote was last
+        // fetched
+        let last_fetch = fs::metadata(self.repo.path().join("FETCH_HEAD"))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Local>::from);
+
+        Ok(Some(SyncStatus {
+            target,
+            tracked: remote.is_some(),
+            ahead,
+            behind,
+            uncommitted: self.uncommitted_changes()?.files.len(),
+            last_fetch,
+            in_progress: self.is_merging(),
+        }))
+    }
+
+    /// Get the local commits not pushed to the remote branch yet, most recent
+    /// first
+    pub fn unpushed_commits(&self, git_config: &GitConfig) -> Result<Vec<CommitInfo>> {
+        let target = self.sync_target(git_config)?;
+        let Ok(local) = self.repo.refname_to_id(&target.local_ref()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        revwalk.push(local)?;
+        if let Ok(remote) = self.repo.refname_to_id(&target.tracking_ref()) {
+            revwalk.hide(remote)?;
+        }
+
+        revwalk
+            .map(|id| self.commit_info(&self.repo.find_commit(id?)?))
+            .collect()
+    }
+
+    /// Get the remote branch the current branch is synced with
+    ///
+    /// The remote and branch configured in `git.remote` and `git.branch` come
+    /// first, then the upstream tracking configuration of the current branch.
+    /// Otherwise the branch of the same name on `origin` is used. Fails if
+    /// HEAD is detached.
+    pub fn sync_target(&self, git_config: &GitConfig) -> Result<SyncTarget> {
+        let local_branch = self.current_branch()?.context(
+            "HEAD is detached from any branch, check out a branch in the tasks repository to sync",
+        )?;
+        let (upstream_remote, upstream_branch) = self.upstream(&local_branch)?;
+
+        let setting = |value: &str| Some(value.to_string()).filter(|value| !value.is_empty());
+        Ok(SyncTarget {
+            remote: setting(&git_config.remote)
+                .or(upstream_remote)
+                .unwrap_or_else(|| DEFAULT_REMOTE.to_string()),
+            remote_branch: setting(&git_config.branch)
+                .or(upstream_branch)
+                .unwrap_or_else(|| local_branch.clone()),
+            local_branch,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_manage_remotes() {
+        let temp_dir = tempdir().unwrap();
+        let git_repo = GitRepo::init(temp_dir.path()).unwrap();
+        assert!(git_repo.remotes().unwrap().is_empty());
+
+        git_repo
+            .add_remote("origin", "https://example.com/tasks.git")
+            .unwrap();
+        assert!(
+            git_repo
+                .add_remote("origin", "https://example.com/other.git")
+                .is_err()
+        );
+        git_repo
+            .set_remote_url("origin", "https://example.com/moved.git")
+            .unwrap();
+        assert!(
+            git_repo
+                .set_remote_url("missing", "https://example.com/tasks.git")
+                .is_err()
+        );
+
+        assert_eq!(
+            git_repo.remotes().unwrap(),
+            vec![RemoteInfo {
+                name: "origin".to_string(),
+                url: Some("https://example.com/moved.git".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sync_target() {
+        let temp_dir = tempdir().unwrap();
+        let git_repo = GitRepo::init(temp_dir.path()).unwrap();
+        fs::write(temp_dir.path().join("task.toml"), "id = \"task\"").unwrap();
+        git_repo.commit_changes("Initial commit").unwrap();
+        let repo = &git_repo.repo;
+        let local_branch = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        // Same branch on origin by default
+        let target = git_repo.sync_target(&GitConfig::default()).unwrap();
+        assert_eq!(target.remote, "origin");
+        assert_eq!(target.remote_branch, local_branch);
+        assert_eq!(
+            target.tracking_ref(),
+            format!("refs/remotes/origin/{local_branch}")
+        );
+
+        // Upstream tracking configuration is honoured
+        let mut config = repo.config().unwrap();
+        config
+            .set_str(&format!("branch.{local_branch}.remote"), "upstream")
+            .unwrap();
+        config
+            .set_str(&format!("branch.{local_branch}.merge"), "refs/heads/trunk")
+            .unwrap();
+        let target = git_repo.sync_target(&GitConfig::default()).unwrap();
+        assert_eq!(target.remote, "upstream");
+        assert_eq!(target.remote_branch, "trunk");
+        assert_eq!(
+            git_repo.default_remote(&GitConfig::default()).unwrap(),
+            "upstream"
+        );
+
+        // But the configuration comes first
+        let git_config = GitConfig {
+            remote: "backup".to_string(),
+            branch: "tasks".to_string(),
+            ..Default::default()
+        };
+        let target = git_repo.sync_target(&git_config).unwrap();
+        assert_eq!(target.remote, "backup");
+        assert_eq!(target.remote_branch, "tasks");
+        assert_eq!(target.local_branch, local_branch);
+
+        // A detached HEAD is not synced
+        let head = repo.head().unwrap().target().unwrap();
+        repo.set_head_detached(head).unwrap();
+        assert!(git_repo.sync_target(&GitConfig::default()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod config;
 pub mod display;
+pub mod error;
 pub mod git;
 pub mod logging;
 pub mod task;
@@ -7,6 +8,7 @@ pub mod task;
 // Re-export commonly used items
 pub use config::Config;
 pub use display::Display;
+pub use error::{Error, Result};
 pub use git::MergeStrategy;
 pub use task::{
     DateRange, Priority, SortCriteria, SortOptions, SortOrder, Task, TaskManager, TaskStatus,
@@ -4,9 +4,11 @@ use std::{
     path::Path,
 };
 
-use anyhow::{Context, Result};
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
+use crate::Result;
+
 /// Active Task information
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ActiveTask {
@@ -28,6 +28,40 @@ pub struct Filter {
 
     /// Enable fuzzy matching for description
     pub fuzzy: Option<String>,
+
+    /// Filter by whether tasks are snoozed
+    pub snoozed: Option<bool>,
+}
+
+impl Filter {
+    /// Check if no filter condition is set
+    pub const fn is_empty(&self) -> bool {
+        self.priority.is_none()
+            && self.task_scope.is_none()
+            && self.task_type.is_none()
+            && self.status.is_none()
+            && self.creation_time.is_none()
+            && self.update_time.is_none()
+            && self.completion_time.is_none()
+            && self.fuzzy.is_none()
+            && self.snoozed.is_none()
+    }
+
+    /// Fill the conditions not set with the ones of another filter
+    #[must_use]
+    pub fn or(self, defaults: Filter) -> Self {
+        Self {
+            priority: self.priority.or(defaults.priority),
+            task_scope: self.task_scope.or(defaults.task_scope),
+            task_type: self.task_type.or(defaults.task_type),
+            status: self.status.or(defaults.status),
+            creation_time: self.creation_time.or(defaults.creation_time),
+            update_time: self.update_time.or(defaults.update_time),
+            completion_time: self.completion_time.or(defaults.completion_time),
+            fuzzy: self.fuzzy.or(defaults.fuzzy),
+            snoozed: self.snoozed.or(defaults.snoozed),
+        }
+    }
 }
 
 /// DateRange struct for robust date parsing
@@ -88,10 +122,13 @@ mod tests {
             scope: scope.map(|s| s.to_string()),
             task_type: task_type.map(|t| t.to_string()),
             status,
-            created_at: created_at.to_string(),
-            updated_at: updated_at.map(|s| s.to_string()),
-            completed_at: completed_at.map(|s| s.to_string()),
+            created_at: DateTime::parse_from_rfc3339(created_at).unwrap(),
+            updated_at: updated_at.map(|s| DateTime::parse_from_rfc3339(s).unwrap()),
+            completed_at: completed_at.map(|s| DateTime::parse_from_rfc3339(s).unwrap()),
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         }
     }
 
@@ -132,10 +169,7 @@ mod tests {
 
                 // Filter by creation time
                 if let Some(ref date_range) = filter.creation_time {
-                    let created_at = match DateTime::parse_from_rfc3339(&task.created_at) {
-                        Ok(dt) => dt.with_timezone(&Local),
-                        Err(_) => return false, // Skip tasks with invalid dates
-                    };
+                    let created_at = task.created_at.with_timezone(&Local);
 
                     if let Some(from) = date_range.from
                         && created_at < from
@@ -153,11 +187,8 @@ mod tests {
                 // Filter by update time
                 if let Some(ref date_range) = filter.update_time {
                     match &task.updated_at {
-                        Some(updated_at_str) => {
-                            let updated_at = match DateTime::parse_from_rfc3339(updated_at_str) {
-                                Ok(dt) => dt.with_timezone(&Local),
-                                Err(_) => return false, // Skip tasks with invalid dates
-                            };
+                        Some(updated_at) => {
+                            let updated_at = updated_at.with_timezone(&Local);
 
                             if let Some(from) = date_range.from
                                 && updated_at < from
@@ -178,12 +209,8 @@ mod tests {
                 // Filter by completion time
                 if let Some(ref date_range) = filter.completion_time {
                     match &task.completed_at {
-                        Some(completed_at_str) => {
-                            let completed_at = match DateTime::parse_from_rfc3339(completed_at_str)
-                            {
-                                Ok(dt) => dt.with_timezone(&Local),
-                                Err(_) => return false, // Skip tasks with invalid dates
-                            };
+                        Some(completed_at) => {
+                            let completed_at = completed_at.with_timezone(&Local);
 
                             if let Some(from) = date_range.from
                                 && completed_at < from
@@ -230,6 +257,31 @@ mod tests {
         assert!(filter.update_time.is_none());
         assert!(filter.completion_time.is_none());
         assert!(filter.fuzzy.is_none());
+        assert!(filter.is_empty());
+
+        let filter = Filter {
+            status: Some(TaskStatus::Todo),
+            ..Default::default()
+        };
+        assert!(!filter.is_empty());
+    }
+
+    #[test]
+    fn test_filter_defaults() {
+        let filter = Filter {
+            task_scope: Some("given".to_string()),
+            ..Default::default()
+        }
+        .or(Filter {
+            task_scope: Some("default".to_string()),
+            status: Some(TaskStatus::Todo),
+            ..Default::default()
+        });
+
+        // Given conditions are kept, the others come from the defaults
+        assert_eq!(filter.task_scope.as_deref(), Some("given"));
+        assert_eq!(filter.status, Some(TaskStatus::Todo));
+        assert!(filter.priority.is_none());
     }
 
     #[test]
@@ -0,0 +1,397 @@
+use std::path::Path;
+
+use chrono::Duration;
+use git2::Oid;
+
+use super::{
+    DateRange, Priority, Task, TaskStatus, active_task::ActiveTask, migration, storage::TRASH_DIR,
+};
+use crate::{
+    Result,
+    git::{
+        history::CommitInfo,
+        repo::{CommitMessage, GitRepo},
+    },
+};
+
+/// Kind of change made to a task
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChangeKind {
+    /// Task file is created
+    Added,
+    /// Task file is updated
+    Modified,
+    /// Task file is removed
+    Deleted,
+}
+
+/// A change made to a task between two commits
+#[derive(Clone, Debug)]
+pub struct TaskChange {
+    /// Task before the change, `None` if it did not exist
+    pub old: Option<Task>,
+    /// Task after the change, `None` if it no longer exists
+    pub new: Option<Task>,
+    /// Whether the change concerns the copy of the task in the trash
+    pub in_trash: bool,
+}
+
+impl TaskChange {
+    /// Get the kind of the change
+    pub const fn kind(&self) -> ChangeKind {
+        match (&self.old, &self.new) {
+            (None, _) => ChangeKind::Added,
+            (Some(_), Some(_)) => ChangeKind::Modified,
+            (Some(_), None) => ChangeKind::Deleted,
+        }
+    }
+
+    /// Get the most recent version of the changed task
+    pub fn task(&self) -> &Task {
+        self.new
+            .as_ref()
+            .or(self.old.as_ref())
+            .expect("Task change should have at least one version")
+    }
+}
+
+/// Event in the history of a task
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskEvent {
+    /// Task is created
+    Created,
+    /// Task is deleted
+    Deleted,
+    /// Task is restored after being deleted
+    Restored,
+    /// Task is permanently removed from the trash
+    Purged,
+    /// Description is edited
+    Description(String, String),
+    /// Priority is changed
+    Priority(Priority, Priority),
+    /// Status is changed
+    Status(TaskStatus, TaskStatus),
+    /// Scope is changed
+    Scope(Option<String>, Option<String>),
+    /// Type is changed
+    TaskType(Option<String>, Option<String>),
+    /// Time is added to the time spent, in seconds
+    TimeAdded(i64),
+}
+
+/// Entry in the history of a task
+#[derive(Clone, Debug)]
+pub struct TaskLogEntry {
+    /// Commit that changed the task
+    pub commit: CommitInfo,
+    /// Task after the commit, `None` if it no longer exists
+    pub task: Option<Task>,
+    /// Changes made to the task by the commit
+    pub events: Vec<TaskEvent>,
+}
+
+/// Task touched by a commit in the activity log
+#[derive(Clone, Debug)]
+pub struct ActivityTask {
+    /// Task ID
+    pub id: String,
+    /// Task description, `None` if the task cannot be found
+    pub description: Option<String>,
+}
+
+/// Commit recorded in the activity log
+#[derive(Clone, Debug)]
+pub struct ActivityEntry {
+    /// Commit information
+    pub commit: CommitInfo,
+    /// Parsed commit message
+    pub message: CommitMessage,
+    /// Tasks touched by the commit
+    pub tasks: Vec<ActivityTask>,
+}
+
+/// Activity log entries sharing the same action and scope
+#[derive(Clone, Debug)]
+pub struct ActivityGroup {
+    /// The type of change (e.g., create, update, finish)
+    pub action: String,
+    /// The scope of the changed tasks
+    pub scope: Option<String>,
+    /// Entries in the group, oldest first
+    pub entries: Vec<ActivityEntry>,
+}
+
+/// Plan for undoing the most recent commits
+#[derive(Clone, Debug)]
+pub struct UndoPlan {
+    /// Commits to undo, most recent first
+    pub commits: Vec<CommitInfo>,
+    /// Commit to go back to, `None` to go back before the initial commit
+    pub target: Option<Oid>,
+    /// Changes made to tasks by undoing
+    pub changes: Vec<TaskChange>,
+    /// Task that was active before the commits and should be active again
+    pub active_task: Option<ActiveTask>,
+}
+
+impl UndoPlan {
+    /// Check if any of the commits has already been pushed
+    pub fn is_pushed(&self) -> bool {
+        self.commits.iter().any(|commit| commit.pushed)
+    }
+}
+
+/// Read a task as it was at the given commit, if any
+///
+/// Files that cannot be parsed as a task are treated as missing.
+fn read_task(git_repo: &GitRepo, id: Option<Oid>, path: &Path) -> Result<Option<Task>> {
+    Ok(git_repo
+        .read_file(id, path)?
+        .and_then(|content| migration::parse_task(&content).ok())
+        .map(|(task, _)| task))
+}
+
+/// Collect changes made to tasks between two commits
+///
+/// A missing commit stands for the empty state before the initial commit.
+pub fn task_changes(
+    git_repo: &GitRepo,
+    from: Option<Oid>,
+    to: Option<Oid>,
+) -> Result<Vec<TaskChange>> {
+    let mut changes = Vec::new();
+    for path in git_repo.changed_files(from, to)? {
+        if path.extension().and_then(|s| s.to_str()) != Some("toml") {
+            continue;
+        }
+        // Only task files at the top level or in the trash are considered
+        let in_trash = match path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => false,
+            Some(parent) if parent == Path::new(TRASH_DIR) => true,
+            _ => continue,
+        };
+
+        let old = read_task(git_repo, from, &path)?;
+        let new = read_task(git_repo, to, &path)?;
+        if old.is_none() && new.is_none() {
+            continue;
+        }
+        changes.push(TaskChange { old, new, in_trash });
+    }
+
+    Ok(changes)
+}
+
+/// Find the task that was active right before the given commits
+///
+/// Stopping a task is the only change that updates its time spent, so the
+/// start time is recovered from the time of the oldest such commit. The
+/// commits are expected to be ordered from the most recent to the oldest.
+pub fn find_active_task(git_repo: &GitRepo, commits: &[CommitInfo]) -> Result<Option<ActiveTask>> {
+    let Some(oldest) = commits.last() else {
+        return Ok(None);
+    };
+
+    for commit in commits.iter().rev() {
+        for change in task_changes(git_repo, commit.parent, Some(commit.id))? {
+            let (Some(old), Some(new)) = (&change.old, &change.new) else {
+                continue;
+            };
+            if change.in_trash || new.time_spent == old.time_spent {
+                continue;
+            }
+            let added = new.time_spent.unwrap_or(0) as i64 - old.time_spent.unwrap_or(0) as i64;
+
+            // The task was only active before the commits if it was started
+            // before the oldest one
+            let started_at = commit.time - Duration::seconds(added);
+            return Ok((started_at <= oldest.time)
+                .then(|| ActiveTask::new(new.id.clone(), started_at.to_rfc3339())));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Compare two versions of a task field by field
+pub fn field_changes(old: &Task, new: &Task) -> Vec<TaskEvent> {
+    let mut events = Vec::new();
+    if old.status != new.status {
+        events.push(TaskEvent::Status(old.status, new.status));
+    }
+    if old.priority != new.priority {
+        events.push(TaskEvent::Priority(old.priority, new.priority));
+    }
+    if old.description != new.description {
+        events.push(TaskEvent::Description(
+            old.description.clone(),
+            new.description.clone(),
+        ));
+    }
+    if old.scope != new.scope {
+        events.push(TaskEvent::Scope(old.scope.clone(), new.scope.clone()));
+    }
+    if old.task_type != new.task_type {
+        events.push(TaskEvent::TaskType(
+            old.task_type.clone(),
+            new.task_type.clone(),
+        ));
+    }
+    if old.time_spent != new.time_spent {
+        let added = new.time_spent.unwrap_or(0) as i64 - old.time_spent.unwrap_or(0) as i64;
+        events.push(TaskEvent::TimeAdded(added));
+    }
+    events
+}
+
+/// Collect the history of a task from the git log, oldest first
+///
+/// Both the task file and its copy in the trash are followed, so the history
+/// covers deletions and restorations as well.
+pub fn task_log(git_repo: &GitRepo, task_id: &str) -> Result<Vec<TaskLogEntry>> {
+    let path = Path::new(task_id).with_extension("toml");
+    let trash_path = Path::new(TRASH_DIR).join(&path);
+    let read = |id: Option<Oid>, path: &Path| read_task(git_repo, id, path);
+
+    let mut entries = Vec::new();
+    for commit in git_repo.history()? {
+        let old = read(commit.parent, &path)?;
+        let new = read(Some(commit.id), &path)?;
+        let old_trashed = read(commit.parent, &trash_path)?.is_some();
+        let new_trashed = read(Some(commit.id), &trash_path)?.is_some();
+
+        let events = match (&old, &new) {
+            (None, Some(_)) if entries.is_empty() => vec![TaskEvent::Created],
+            (None, Some(_)) => vec![TaskEvent::Restored],
+            (Some(_), None) => vec![TaskEvent::Deleted],
+            (Some(old), Some(new)) => field_changes(old, new),
+            (None, None) if old_trashed && !new_trashed => vec![TaskEvent::Purged],
+            (None, None) => vec![],
+        };
+        if !events.is_empty() {
+            entries.push(TaskLogEntry {
+                commit,
+                task: new,
+                events,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Find the description of a task touched by a commit
+///
+/// The task is looked up at the commit and right before it, both at the top
+/// level and in the trash, so deleted tasks are found as well.
+fn touched_task(git_repo: &GitRepo, commit: &CommitInfo, task_id: &str) -> Result<ActivityTask> {
+    let path = Path::new(task_id).with_extension("toml");
+    let trash_path = Path::new(TRASH_DIR).join(&path);
+
+    let mut description = None;
+    for id in [Some(commit.id), commit.parent].into_iter().flatten() {
+        for path in [&path, &trash_path] {
+            if description.is_none() {
+                description = read_task(git_repo, Some(id), path)?.map(|task| task.description);
+            }
+        }
+    }
+
+    Ok(ActivityTask {
+        id: task_id.to_string(),
+        description,
+    })
+}
+
+/// Describe a commit with the tasks it touched
+///
+/// Returns `None` if the commit was not made by rutd.
+pub fn activity_entry(git_repo: &GitRepo, commit: CommitInfo) -> Result<Option<ActivityEntry>> {
+    let Some(message) = GitRepo::parse_commit_message(&commit.message) else {
+        return Ok(None);
+    };
+
+    let tasks = message
+        .task_ids
+        .iter()
+        .map(|id| touched_task(git_repo, &commit, id))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(ActivityEntry {
+        commit,
+        message,
+        tasks,
+    }))
+}
+
+/// Collect commits made by rutd within the date range, grouped by action and
+/// scope
+pub fn activity(git_repo: &GitRepo, range: &DateRange) -> Result<Vec<ActivityGroup>> {
+    let mut groups: Vec<ActivityGroup> = Vec::new();
+    for commit in git_repo.history()? {
+        if range.from.is_some_and(|from| commit.time < from)
+            || range.to.is_some_and(|to| commit.time >= to)
+        {
+            continue;
+        }
+        // Skip commits not made by rutd
+        let Some(entry) = activity_entry(git_repo, commit)? else {
+            continue;
+        };
+
+        match groups.iter_mut().find(|group| {
+            group.action == entry.message.action && group.scope == entry.message.scope
+        }) {
+            Some(group) => group.entries.push(entry),
+            None => groups.push(ActivityGroup {
+                action: entry.message.action.clone(),
+                scope: entry.message.scope.clone(),
+                entries: vec![entry],
+            }),
+        }
+    }
+
+    groups.sort_by(|a, b| (&a.action, &a.scope).cmp(&(&b.action, &b.scope)));
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_changes() {
+        let old = Task::new(
+            "test-id".to_string(),
+            "Old description".to_string(),
+            Priority::Normal,
+            None,
+            None,
+        );
+        let mut new = old.clone();
+
+        // No changes
+        assert!(field_changes(&old, &new).is_empty());
+
+        new.status = TaskStatus::Done;
+        new.priority = Priority::High;
+        new.description = "New description".to_string();
+        new.scope = Some("scope".to_string());
+        new.time_spent = Some(90);
+
+        let events = field_changes(&old, &new);
+        assert_eq!(
+            events,
+            vec![
+                TaskEvent::Status(TaskStatus::Todo, TaskStatus::Done),
+                TaskEvent::Priority(Priority::Normal, Priority::High),
+                TaskEvent::Description(
+                    "Old description".to_string(),
+                    "New description".to_string()
+                ),
+                TaskEvent::Scope(None, Some("scope".to_string())),
+                TaskEvent::TimeAdded(90),
+            ]
+        );
+    }
+}
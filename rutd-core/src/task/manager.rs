@@ -1,5 +1,12 @@
-use anyhow::{Context, Result};
-use chrono::{DateTime, Local};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, Local};
 use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
 use uuid::Uuid;
 
@@ -7,13 +14,25 @@ use super::{
     SortOptions,
     active_task::{self, ActiveTask},
     filter::{DateRange, Filter},
-    model::{Priority, Task},
-    sort_tasks, storage,
+    history::{self, ActivityEntry, ActivityGroup, TaskLogEntry, UndoPlan},
+    merge::TaskConflict,
+    migration,
+    model::{Annotation, Modification, Priority, Task, TaskStatus},
+    pending_sync::{self, PendingSync},
+    sort_tasks,
+    storage::{self, InvalidTaskFile},
+    working_set::{self, WorkingSet},
 };
 use crate::{
-    config::{GitConfig, PathConfig},
+    Error, Result,
+    config::{AutoCommit, AutoSync, GitConfig, PathConfig},
     display::Display,
-    git::{MergeStrategy, repo::GitRepo},
+    git::{
+        MergeStrategy,
+        changes::{ChangeSet, FileChange},
+        remote::{RemoteInfo, SyncStatus, SyncTarget},
+        repo::{CommitMessage, GitRepo},
+    },
 };
 
 /// Task Manager
@@ -21,17 +40,393 @@ use crate::{
 pub struct TaskManager {
     path_config: PathConfig,
     git_config: GitConfig,
+    /// Whether changes are only recorded instead of being written
+    dry_run: bool,
+    /// Changes recorded in a dry run
+    pending: RefCell<Vec<ChangeSet>>,
+    /// Changes staged by the ongoing transaction, if any
+    staged: RefCell<Option<Vec<ChangeSet>>>,
+    /// Whether tasks were changed, to know if there is anything to sync
+    changed: Cell<bool>,
+    /// Task files that could not be loaded by the last listing
+    invalid_files: RefCell<Vec<InvalidTaskFile>>,
 }
 
 // Helper functions for TaskManager
 impl TaskManager {
     /// Check if time fits in the date range
-    fn is_time_in_range(time: &str, range: &DateRange) -> bool {
-        let time = DateTime::parse_from_rfc3339(time)
-            .unwrap()
-            .with_timezone(&Local);
-        range.from.map(|from| time >= from).unwrap_or(true)
-            && range.to.map(|to| time < to).unwrap_or(true)
+    fn is_time_in_range(time: &DateTime<FixedOffset>, range: &DateRange) -> bool {
+        range.from.map(|from| *time >= from).unwrap_or(true)
+            && range.to.map(|to| *time < to).unwrap_or(true)
+    }
+
+    /// Commit changes to task files, or only record them in a dry run
+    ///
+    /// In a transaction, the files are written right away but only committed
+    /// when the transaction ends.
+    fn commit(&self, mut changes: ChangeSet) -> Result<()> {
+        // Files changed earlier in a dry run are not written, so their content
+        // before this change is the one recorded then
+        if self.dry_run {
+            for file in &mut changes.files {
+                if let Some(planned) = self.planned_content(&file.path) {
+                    file.old = planned;
+                }
+            }
+        }
+
+        if let Some(staged) = self.staged.borrow_mut().as_mut() {
+            log::debug!("Staging changes: {}", changes.message);
+            staged.push(changes);
+            if !self.dry_run {
+                let task_dir = self.path_config.task_dir_path();
+                staged
+                    .last()
+                    .map_or(Ok(()), |changes| changes.write_files(&task_dir))?;
+            }
+            return Ok(());
+        }
+
+        if self.dry_run {
+            log::debug!("Dry run, not committing: {}", changes.message);
+            self.pending.borrow_mut().push(changes);
+            return Ok(());
+        }
+        self.changed.set(true);
+        if self.git_config.auto_commit != AutoCommit::Always {
+            log::debug!("Auto-commit disabled, not committing: {}", changes.message);
+            return changes.write_files(&self.path_config.task_dir_path());
+        }
+        fs::create_dir_all(self.path_config.task_dir_path())?;
+        self.git_repo()?.apply(&changes)
+    }
+
+    /// Open the tasks repository, creating it if needed except in a dry run
+    fn git_repo(&self) -> Result<GitRepo> {
+        let task_dir = self.path_config.task_dir_path();
+        let git_repo = if self.dry_run {
+            GitRepo::open(task_dir)?
+        } else {
+            GitRepo::init(task_dir)?
+        };
+        Ok(git_repo.with_config(&self.git_config))
+    }
+
+    /// Open the tasks repository to read it, without ever creating it
+    ///
+    /// Returns `None` if there is no tasks repository yet.
+    fn existing_git_repo(&self) -> Result<Option<GitRepo>> {
+        let task_dir = self.path_config.task_dir_path();
+        if !task_dir.join(".git").exists() {
+            return Ok(None);
+        }
+        Ok(Some(GitRepo::open(task_dir)?.with_config(&self.git_config)))
+    }
+
+    /// Get the content of a task file as changed earlier in a dry run
+    ///
+    /// Returns `None` if the file was not changed, and `Some(None)` if it was
+    /// removed.
+    fn planned_content(&self, path: &Path) -> Option<Option<String>> {
+        let staged = self.staged.borrow();
+        let pending = self.pending.borrow();
+        staged
+            .iter()
+            .flatten()
+            .rev()
+            .chain(pending.iter().rev())
+            .find_map(|changes| changes.files.iter().find(|file| file.path == path))
+            .map(|file| file.new.clone())
+    }
+
+    /// Load a task, including the changes made to it earlier in a dry run
+    fn load_task(&self, task_id: &str) -> Result<Task> {
+        let task = storage::load_task(&self.path_config.task_dir_path(), task_id)?;
+        if !self.dry_run {
+            return Ok(task);
+        }
+        match self.planned_content(Path::new(&format!("{}.toml", task.id))) {
+            None => Ok(task),
+            Some(Some(content)) => Ok(migration::parse_task(&content)?.0),
+            Some(None) => Err(Error::NotFound(task_id.to_string())),
+        }
+    }
+
+    /// Save a task with a commit
+    fn save_task(&self, task: &Task, after_action: &str, description: &str) -> Result<()> {
+        self.commit(storage::plan_save_tasks(
+            &self.path_config.task_dir_path(),
+            std::slice::from_ref(task),
+            after_action,
+            description,
+        )?)
+    }
+
+    /// Combine the changes staged by a transaction into a single commit
+    ///
+    /// The scope and type are only part of the commit message if they are
+    /// shared by all staged changes.
+    fn combine_changes(staged: &[ChangeSet], action: &str, description: &str) -> ChangeSet {
+        let mut changes = ChangeSet::default();
+        let messages = staged
+            .iter()
+            .map(|staged| {
+                changes.extend(staged);
+                GitRepo::parse_commit_message(&staged.message)
+            })
+            .collect::<Vec<_>>();
+
+        let shared = |field: fn(&CommitMessage) -> &Option<String>| {
+            let first = messages.first()?.as_ref().map(field)?;
+            messages
+                .iter()
+                .all(|message| message.as_ref().map(field) == Some(first))
+                .then_some(first.as_deref())
+                .flatten()
+        };
+        let mut ids = Vec::new();
+        for message in messages.iter().flatten() {
+            for id in &message.task_ids {
+                if !ids.contains(&id.as_str()) {
+                    ids.push(id.as_str());
+                }
+            }
+        }
+
+        changes.message = GitRepo::generate_commit_message(
+            action,
+            shared(|message| &message.scope),
+            shared(|message| &message.task_type),
+            description,
+            &ids.join("\n"),
+        );
+        changes
+    }
+
+    /// Record the active task, except in a dry run
+    fn save_active_task(&self, active: &ActiveTask) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        active_task::save_active_task(&self.path_config.active_task_file_path(), active)
+    }
+
+    /// Clear the active task, except in a dry run
+    fn clear_active_task(&self) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        active_task::clear_active_task(&self.path_config.active_task_file_path())
+    }
+
+    /// Resolve a short task number to the full task ID
+    ///
+    /// Other IDs are returned unchanged, to be matched as ID prefixes.
+    fn resolve_id(&self, task_id: &str) -> Result<String> {
+        Ok(self.working_set()?.resolve(task_id)?.to_string())
+    }
+
+    /// Find the full ID of a task in the given directories, then in the git
+    /// history if `history` is set
+    fn find_task_id(&self, task_id: &str, dirs: &[PathBuf], history: bool) -> Result<String> {
+        for dir in dirs.iter().filter(|dir| dir.exists()) {
+            match storage::load_task(dir, task_id) {
+                Ok(task) => return Ok(task.id),
+                Err(Error::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if history
+            && let Some(git_repo) = self.existing_git_repo()?
+            && let Some(content) = git_repo.find_deleted_task(task_id)?
+        {
+            return Ok(migration::parse_task(&content)?.0.id);
+        }
+        Err(Error::NotFound(task_id.to_string()))
+    }
+
+    /// Resolve a task number or ID prefix to the full ID of a task that is not
+    /// deleted
+    ///
+    /// Fails with [`Error::AmbiguousId`] if the prefix matches several tasks.
+    pub fn resolve_task_id(&self, task_id: &str) -> Result<String> {
+        let task_id = self.resolve_id(task_id)?;
+        self.find_task_id(&task_id, &[self.path_config.task_dir_path()], false)
+    }
+
+    /// Resolve an ID prefix to the full ID of a task in the trash or the git
+    /// history
+    ///
+    /// Fails with [`Error::AmbiguousId`] if the prefix matches several tasks
+    /// in the trash.
+    pub fn resolve_deleted_task_id(&self, task_id: &str) -> Result<String> {
+        let task_dir = self.path_config.task_dir_path();
+        self.find_task_id(task_id, &[storage::trash_dir(&task_dir)], true)
+    }
+
+    /// Resolve a task number or ID prefix to the full ID of any task, deleted
+    /// or not
+    ///
+    /// Fails with [`Error::AmbiguousId`] if the prefix matches several tasks
+    /// that are not deleted, or several tasks in the trash.
+    pub fn resolve_any_task_id(&self, task_id: &str) -> Result<String> {
+        let task_id = self.resolve_id(task_id)?;
+        let task_dir = self.path_config.task_dir_path();
+        let trash_dir = storage::trash_dir(&task_dir);
+        self.find_task_id(&task_id, &[task_dir, trash_dir], true)
+    }
+
+    /// Load the tasks matching an ambiguous ID, e.g. to choose one of them
+    ///
+    /// Tasks are looked up among tasks that are not deleted, then in the
+    /// trash. Candidates that cannot be found are skipped.
+    pub fn candidate_tasks(&self, candidates: &[String]) -> Result<Vec<Task>> {
+        let task_dir = self.path_config.task_dir_path();
+        let trash_dir = storage::trash_dir(&task_dir);
+        let mut tasks = Vec::new();
+        for id in candidates {
+            for dir in [&task_dir, &trash_dir] {
+                if dir.join(format!("{id}.toml")).is_file() {
+                    tasks.push(storage::load_task(dir, id)?);
+                    break;
+                }
+            }
+        }
+        Ok(tasks)
+    }
+
+    /// Load all tasks, keeping the task files that could not be loaded
+    fn load_all_tasks(&self) -> Result<Vec<Task>> {
+        let (tasks, invalid_files) =
+            storage::load_all_tasks_checked(&self.path_config.task_dir_path())?;
+        *self.invalid_files.borrow_mut() = invalid_files;
+        Ok(tasks)
+    }
+
+    /// Sync with the remote repository on behalf of `git.auto_sync`
+    ///
+    /// A failed sync is undone and recorded to be retried later.
+    fn run_auto_sync<D: Display>(&self, git_repo: &GitRepo, display_manager: &D) -> Result<()> {
+        log::info!("Syncing automatically with remote repository");
+        GitRepo::set_network_timeout(self.git_config.sync_timeout())?;
+        if let Err(e) = self.sync(MergeStrategy::None, display_manager) {
+            // Leave the tasks as they were before the sync
+            if git_repo.is_merging() {
+                git_repo.abort_sync()?;
+            }
+            log::warn!("Automatic sync failed: {e:#}");
+            pending_sync::save_pending_sync(
+                &self.path_config.pending_sync_file_path(),
+                &PendingSync::new(Local::now().to_rfc3339(), format!("{e:#}")),
+            )?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Add the time spent since the task was started to the task
+    fn add_time_spent(task: &mut Task, active_task: &ActiveTask) -> Result<()> {
+        // Calculate time spent using the active task record
+        let started_time = DateTime::parse_from_rfc3339(&active_task.started_at)
+            .context("Failed to parse started_at time from active task record")?;
+        let now = Local::now();
+        let duration = now.signed_duration_since(started_time.with_timezone(&Local));
+
+        // Calculate total seconds spent
+        let seconds_spent = duration.num_seconds().max(0) as u64;
+
+        task.time_spent = Some(task.time_spent.unwrap_or(0) + seconds_spent);
+        Ok(())
+    }
+
+    /// Select tasks by ID, or by filter only if no ID is given
+    ///
+    /// Tasks selected by ID are kept only if they match the filter. If
+    /// `working` is set, only tasks to do are selected by the filter unless a
+    /// status is given.
+    fn select_tasks(
+        &self,
+        task_ids: &[String],
+        filter_options: &Filter,
+        working: bool,
+    ) -> Result<Vec<Task>> {
+        // Refuse to select every task at once
+        if task_ids.is_empty() && filter_options.is_empty() {
+            return Err(anyhow::anyhow!("No task IDs or filters specified").into());
+        }
+
+        if task_ids.is_empty() {
+            let mut filter_options = filter_options.clone();
+            if working {
+                filter_options.status = filter_options.status.or(Some(TaskStatus::Todo));
+            }
+            return self.list_tasks(&filter_options, None);
+        }
+
+        let mut tasks: Vec<Task> = Vec::new();
+        for task_id in task_ids {
+            let task_id = self.resolve_id(task_id)?;
+            let task = self.load_task(&task_id)?;
+            if !tasks.iter().any(|t| t.id == task.id) {
+                tasks.push(task);
+            }
+        }
+        tasks.retain(|task| Self::matches_filters(task, filter_options));
+        Ok(tasks)
+    }
+
+    /// Apply the same change to several tasks, recorded in a single commit
+    ///
+    /// The user is asked for confirmation when more than one task is
+    /// affected, unless `force` is set. Nothing is saved if the change fails
+    /// for any of the tasks. The active task is stopped if it is affected and
+    /// `stop_active` is set.
+    fn update_tasks<D: Display>(
+        &self,
+        mut tasks: Vec<Task>,
+        (action, description): (&str, &str),
+        stop_active: bool,
+        force: bool,
+        display_manager: &D,
+        update: impl Fn(&mut Task) -> Result<()>,
+    ) -> Result<Vec<String>> {
+        let count = tasks.len();
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Confirm the change if not forced
+        if count > 1 && !force {
+            let message = format!("{description}: {count} tasks affected, continue?");
+            if !display_manager.confirm(&message)? {
+                return Ok(Vec::new());
+            }
+        }
+
+        let active_task_file = self.path_config.active_task_file_path();
+        let active = active_task::load_active_task(&active_task_file)?
+            .filter(|active| stop_active && tasks.iter().any(|t| t.id == active.task_id));
+
+        self.transaction(action, description, |manager| {
+            for task in &mut tasks {
+                update(task)?;
+                if let Some(active) = active.as_ref().filter(|a| a.task_id == task.id) {
+                    Self::add_time_spent(task, active)?;
+                }
+                task.updated_at = Some(Local::now().fixed_offset());
+                manager.save_task(task, action, description)?;
+            }
+            Ok(())
+        })?;
+
+        // Stop the active task once its time is saved
+        if let Some(active) = active {
+            self.clear_active_task()?;
+            log::debug!("Stopped active task: {}", active.task_id);
+        }
+
+        Ok(tasks.into_iter().map(|task| task.id).collect())
     }
 
     /// Check if a task matches the filter conditions
@@ -58,7 +453,7 @@ impl TaskManager {
             // Match update/creation time in range
             && filter_options.update_time.as_ref().is_none_or(|range| {
                 Self::is_time_in_range(
-                    task.updated_at.as_deref().unwrap_or(&task.created_at),
+                    task.updated_at.as_ref().unwrap_or(&task.created_at),
                     range,
                 )
             })
@@ -68,6 +463,10 @@ impl TaskManager {
                     .as_ref()
                     .is_some_and(|completed_at| Self::is_time_in_range(completed_at, range))
             })
+            // Match snoozed tasks
+            && filter_options
+                .snoozed
+                .is_none_or(|snoozed| task.is_snoozed() == snoozed)
             // Match description using fuzzy matching
             && filter_options.fuzzy.as_ref().is_none_or(|q| {
                 q.is_empty()
@@ -85,7 +484,74 @@ impl TaskManager {
         Self {
             path_config,
             git_config,
+            dry_run: false,
+            pending: RefCell::new(Vec::new()),
+            staged: RefCell::new(None),
+            changed: Cell::new(false),
+            invalid_files: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Only record the changes instead of writing them
+    ///
+    /// Changes to task files and the commits that would be created can be
+    /// retrieved with [`TaskManager::take_pending_changes`].
+    #[must_use]
+    pub const fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Get the changes recorded in a dry run, in the order they were made
+    pub fn take_pending_changes(&self) -> Vec<ChangeSet> {
+        self.pending.take()
+    }
+
+    /// Get the task files skipped by the last listing because they could not
+    /// be loaded
+    pub fn take_invalid_files(&self) -> Vec<InvalidTaskFile> {
+        self.invalid_files.take()
+    }
+
+    /// Make several changes to tasks with a single commit
+    ///
+    /// Every save and delete made by `f` is written to the tasks directory
+    /// right away, and all of them are committed at once with the given
+    /// action and description when `f` succeeds. If `f` or the commit fails,
+    /// the task files are restored to their content before the transaction.
+    /// A transaction started inside another one is part of the outer one.
+    pub fn transaction<T>(
+        &self,
+        action: &str,
+        description: &str,
+        f: impl FnOnce(&Self) -> Result<T>,
+    ) -> Result<T> {
+        if self.staged.borrow().is_some() {
+            return f(self);
         }
+
+        self.staged.replace(Some(Vec::new()));
+        let result = f(self);
+        let staged = self.staged.take().unwrap_or_default();
+        let result = result.and_then(|value| {
+            if !staged.is_empty() {
+                self.commit(Self::combine_changes(&staged, action, description))?;
+            }
+            Ok(value)
+        });
+
+        // Roll back the files written so far
+        if result.is_err() && !self.dry_run {
+            let task_dir = self.path_config.task_dir_path();
+            for changes in staged.iter().rev() {
+                if let Err(e) = changes.revert_files(&task_dir) {
+                    log::error!("Failed to roll back changes '{}': {e}", changes.message);
+                }
+            }
+            log::debug!("Rolled back {} staged changes", staged.len());
+        }
+
+        result
     }
 
     /// Add a new task
@@ -104,12 +570,7 @@ impl TaskManager {
             scope,
             task_type,
         );
-        storage::save_task(
-            &self.path_config.task_dir_path(),
-            &task,
-            "create",
-            "Create task",
-        )?;
+        self.save_task(&task, "create", "Create task")?;
         Ok(id)
     }
 
@@ -119,7 +580,7 @@ impl TaskManager {
         filter_options: &Filter,
         sort_options: Option<&SortOptions>,
     ) -> Result<Vec<Task>> {
-        let tasks = storage::load_all_tasks(&self.path_config.task_dir_path())?;
+        let tasks = self.load_all_tasks()?;
         let mut filtered_tasks = tasks
             .into_iter()
             .filter(|task| Self::matches_filters(task, filter_options))
@@ -133,10 +594,33 @@ impl TaskManager {
         Ok(filtered_tasks)
     }
 
+    /// Get the short numbers of working tasks
+    ///
+    /// The working set is computed on first use and then kept until it is
+    /// refreshed, so numbers do not shift while working with tasks.
+    pub fn working_set(&self) -> Result<WorkingSet> {
+        match working_set::load_working_set(&self.path_config.working_set_file_path())? {
+            Some(working_set) => Ok(working_set),
+            None => self.refresh_working_set(),
+        }
+    }
+
+    /// Renumber working tasks, usually each time they are listed
+    pub fn refresh_working_set(&self) -> Result<WorkingSet> {
+        let tasks = self.load_all_tasks()?;
+        let working_set = WorkingSet::from_tasks(&tasks);
+        if !self.dry_run {
+            working_set::save_working_set(&self.path_config.working_set_file_path(), &working_set)?;
+        }
+
+        log::debug!("Numbered {} working tasks", working_set.tasks.len());
+        Ok(working_set)
+    }
+
     /// Mark a task as completed
     pub fn finish_task(&self, task_id: Option<&str>) -> Result<String> {
         let task_id = match (
-            task_id.map(|id| id.to_string()),
+            task_id.map(|id| self.resolve_id(id)).transpose()?,
             active_task::load_active_task(&self.path_config.active_task_file_path())?,
         ) {
             // Stop the task first if matching active task
@@ -150,22 +634,17 @@ impl TaskManager {
             // Fall back to active task if no ID provided
             (None, Some(active_task)) => active_task.task_id,
             // Raise an error if neither provided nor active task found
-            (None, None) => anyhow::bail!("No task ID provided and no active task found"),
+            (None, None) => return Err(Error::NoActiveTask),
         };
-        let mut task = storage::load_task(&self.path_config.task_dir_path(), &task_id)?;
+        let mut task = self.load_task(&task_id)?;
 
         // Update task status and timestamps
         task.status = task.status.done()?;
-        task.updated_at = Some(Local::now().to_rfc3339());
-        task.completed_at = Some(Local::now().to_rfc3339());
+        task.updated_at = Some(Local::now().fixed_offset());
+        task.completed_at = Some(Local::now().fixed_offset());
 
         // Save the updated task
-        storage::save_task(
-            &self.path_config.task_dir_path(),
-            &task,
-            "finish",
-            "Mark task as done",
-        )?;
+        self.save_task(&task, "finish", "Mark task as done")?;
 
         log::debug!("Completed task: {task_id}");
 
@@ -174,13 +653,14 @@ impl TaskManager {
 
     /// Start working on a task
     pub fn start_task(&self, task_id: &str) -> Result<String> {
-        let task = storage::load_task(&self.path_config.task_dir_path(), task_id)?;
+        let task_id = &self.resolve_id(task_id)?;
+        let task = self.load_task(task_id)?;
 
         // Check if there is already an active task
         match active_task::load_active_task(&self.path_config.active_task_file_path())? {
-            Some(active) if active.task_id == task_id => {
+            Some(active) if active.task_id == *task_id => {
                 // If the task is already active, return an error
-                anyhow::bail!("Task {task_id} is already active.")
+                return Err(anyhow::anyhow!("Task {task_id} is already active.").into());
             }
             Some(active) => {
                 // If there's an active task, stop it first
@@ -201,12 +681,23 @@ impl TaskManager {
 
         // Create and save active task record
         let active = ActiveTask::new(task.id.clone(), now);
-        active_task::save_active_task(&self.path_config.active_task_file_path(), &active)?;
+        self.save_active_task(&active)?;
 
         log::debug!("Started task: {} and saved to active task file", task.id);
         Ok(task.id)
     }
 
+    /// Get the task being worked on, along with when it was started
+    pub fn active_task(&self) -> Result<Option<(Task, ActiveTask)>> {
+        let Some(active) =
+            active_task::load_active_task(&self.path_config.active_task_file_path())?
+        else {
+            return Ok(None);
+        };
+        let task = self.load_task(&active.task_id)?;
+        Ok(Some((task, active)))
+    }
+
     /// Stop working on a task
     pub fn stop_task(&self) -> Result<String> {
         // Check if there's an active task
@@ -214,38 +705,23 @@ impl TaskManager {
             active_task::load_active_task(&self.path_config.active_task_file_path())?
         else {
             // No active task found
-            anyhow::bail!("No active task found. Task might not be in progress.")
+            return Err(Error::NoActiveTask);
         };
 
         // Load the task
-        let mut task =
-            storage::load_task(&self.path_config.task_dir_path(), &active_task_info.task_id)?;
-
-        // Calculate time spent using the active task record
-        let started_time = DateTime::parse_from_rfc3339(&active_task_info.started_at)
-            .context("Failed to parse started_at time from active task record")?;
-        let now = Local::now();
-        let duration = now.signed_duration_since(started_time.with_timezone(&Local));
-
-        // Calculate total seconds spent
-        let seconds_spent = duration.num_seconds().max(0) as u64;
+        let mut task = self.load_task(&active_task_info.task_id)?;
 
         // Update task time spent
-        task.time_spent = Some(task.time_spent.unwrap_or(0) + seconds_spent);
+        Self::add_time_spent(&mut task, &active_task_info)?;
 
         // Update task status and timestamps
-        task.updated_at = Some(Local::now().to_rfc3339());
+        task.updated_at = Some(Local::now().fixed_offset());
 
         // Save the updated task
-        storage::save_task(
-            &self.path_config.task_dir_path(),
-            &task,
-            "update",
-            "Update time spent on task",
-        )?;
+        self.save_task(&task, "update", "Update time spent on task")?;
 
         // Clear the active task record
-        active_task::clear_active_task(&self.path_config.active_task_file_path())?;
+        self.clear_active_task()?;
 
         log::debug!(
             "Stopped task: {} and cleared active task file",
@@ -257,7 +733,7 @@ impl TaskManager {
     /// Mark a task as aborted
     pub fn abort_task(&self, task_id: Option<&str>) -> Result<String> {
         let task_id = match (
-            task_id.map(|id| id.to_string()),
+            task_id.map(|id| self.resolve_id(id)).transpose()?,
             active_task::load_active_task(&self.path_config.active_task_file_path())?,
         ) {
             // Stop the task first if matching active task
@@ -271,28 +747,172 @@ impl TaskManager {
             // Fall back to active task if no ID provided
             (None, Some(active_task)) => active_task.task_id,
             // Raise an error if neither provided nor active task found
-            (None, None) => anyhow::bail!("No task ID provided and no active task found"),
+            (None, None) => return Err(Error::NoActiveTask),
         };
-        let mut task = storage::load_task(&self.path_config.task_dir_path(), &task_id)?;
+        let mut task = self.load_task(&task_id)?;
 
         // Update task status and timestamps
         task.status = task.status.aborted()?;
-        task.updated_at = Some(Local::now().to_rfc3339());
-        task.completed_at = Some(Local::now().to_rfc3339());
+        task.updated_at = Some(Local::now().fixed_offset());
+        task.completed_at = Some(Local::now().fixed_offset());
 
         // Save the updated task
-        storage::save_task(
-            &self.path_config.task_dir_path(),
-            &task,
-            "cancel",
-            "Cancel task",
-        )?;
+        self.save_task(&task, "cancel", "Cancel task")?;
 
         log::debug!("Aborted task: {task_id}");
 
         Ok(task_id)
     }
 
+    /// Mark tasks selected by ID or filter as completed
+    ///
+    /// Only tasks to do are selected by the filter unless a status is given.
+    /// Returns the IDs of the completed tasks, none if the user cancels.
+    pub fn finish_tasks<D: Display>(
+        &self,
+        task_ids: &[String],
+        filter_options: &Filter,
+        force: bool,
+        display_manager: &D,
+    ) -> Result<Vec<String>> {
+        let tasks = self.select_tasks(task_ids, filter_options, true)?;
+
+        self.update_tasks(
+            tasks,
+            ("finish", "Mark tasks as done"),
+            true,
+            force,
+            display_manager,
+            |task| {
+                task.status = task.status.done()?;
+                task.completed_at = Some(Local::now().fixed_offset());
+                Ok(())
+            },
+        )
+    }
+
+    /// Mark tasks selected by ID or filter as aborted
+    ///
+    /// Only tasks to do are selected by the filter unless a status is given.
+    /// Returns the IDs of the aborted tasks, none if the user cancels.
+    pub fn abort_tasks<D: Display>(
+        &self,
+        task_ids: &[String],
+        filter_options: &Filter,
+        force: bool,
+        display_manager: &D,
+    ) -> Result<Vec<String>> {
+        let tasks = self.select_tasks(task_ids, filter_options, true)?;
+
+        self.update_tasks(
+            tasks,
+            ("cancel", "Cancel tasks"),
+            true,
+            force,
+            display_manager,
+            |task| {
+                task.status = task.status.aborted()?;
+                task.completed_at = Some(Local::now().fixed_offset());
+                Ok(())
+            },
+        )
+    }
+
+    /// Change the priority, scope or type of tasks selected by ID or filter
+    ///
+    /// Returns the IDs of the modified tasks, none if the user cancels.
+    pub fn modify_tasks<D: Display>(
+        &self,
+        task_ids: &[String],
+        filter_options: &Filter,
+        modification: &Modification,
+        force: bool,
+        display_manager: &D,
+    ) -> Result<Vec<String>> {
+        if modification.is_empty() {
+            return Err(anyhow::anyhow!("No changes specified").into());
+        }
+        let tasks = self.select_tasks(task_ids, filter_options, false)?;
+
+        self.update_tasks(
+            tasks,
+            ("update", "Update tasks"),
+            false,
+            force,
+            display_manager,
+            |task| {
+                modification.apply(task);
+                Ok(())
+            },
+        )
+    }
+
+    /// Hide tasks selected by ID or filter from the task list until the given
+    /// time, or show them again without a time
+    ///
+    /// Only tasks to do are selected by the filter unless a status is given.
+    /// Returns the IDs of the snoozed tasks, none if the user cancels.
+    pub fn snooze_tasks<D: Display>(
+        &self,
+        task_ids: &[String],
+        filter_options: &Filter,
+        until: Option<DateTime<FixedOffset>>,
+        force: bool,
+        display_manager: &D,
+    ) -> Result<Vec<String>> {
+        let tasks = self.select_tasks(task_ids, filter_options, true)?;
+        let description = match until {
+            Some(_) => "Snooze tasks",
+            None => "Unsnooze tasks",
+        };
+
+        self.update_tasks(
+            tasks,
+            ("update", description),
+            false,
+            force,
+            display_manager,
+            |task| {
+                task.snoozed_until = until;
+                Ok(())
+            },
+        )
+    }
+
+    /// Add a note to tasks selected by ID or filter
+    ///
+    /// Returns the IDs of the annotated tasks, none if the user cancels.
+    pub fn annotate_tasks<D: Display>(
+        &self,
+        task_ids: &[String],
+        filter_options: &Filter,
+        text: &str,
+        force: bool,
+        display_manager: &D,
+    ) -> Result<Vec<String>> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(anyhow::anyhow!("No annotation specified").into());
+        }
+        let tasks = self.select_tasks(task_ids, filter_options, false)?;
+        let annotation = Annotation {
+            created_at: Local::now().fixed_offset(),
+            text: text.to_string(),
+        };
+
+        self.update_tasks(
+            tasks,
+            ("update", "Annotate tasks"),
+            false,
+            force,
+            display_manager,
+            |task| {
+                task.annotations.push(annotation.clone());
+                Ok(())
+            },
+        )
+    }
+
     /// Edit task description
     pub fn edit_task_description<D: Display>(
         &self,
@@ -300,11 +920,12 @@ impl TaskManager {
         display_manager: &D,
     ) -> Result<String> {
         // Load the task
-        let mut task = storage::load_task(&self.path_config.task_dir_path(), task_id)?;
+        let task_id = &self.resolve_id(task_id)?;
+        let mut task = self.load_task(task_id)?;
 
         // Edit the task description through display
         let Some(new_description) = display_manager.edit(&task.description)? else {
-            anyhow::bail!("No changes made to the task description");
+            return Err(anyhow::anyhow!("No changes made to the task description").into());
         };
 
         // Trim whitespace
@@ -313,13 +934,8 @@ impl TaskManager {
         // Only update if description has changed
         if new_description != task.description {
             task.description = new_description;
-            task.updated_at = Some(Local::now().to_rfc3339());
-            storage::save_task(
-                &self.path_config.task_dir_path(),
-                &task,
-                "update",
-                "Update task description",
-            )?;
+            task.updated_at = Some(Local::now().fixed_offset());
+            self.save_task(&task, "update", "Update task description")?;
         }
 
         Ok(task.id)
@@ -346,34 +962,565 @@ impl TaskManager {
         }
 
         // Batch delete tasks
-        storage::delete_task(
+        self.commit(storage::plan_delete_task(
             &self.path_config.task_dir_path(),
             &tasks
                 .iter()
                 .map(|task| task.id.as_str())
                 .collect::<Vec<_>>(),
-        )?;
+        )?)?;
 
         Ok(count)
     }
 
+    /// List tasks in the trash
+    pub fn list_trash(&self, sort_options: Option<&SortOptions>) -> Result<Vec<Task>> {
+        let mut tasks = storage::load_trashed_tasks(&self.path_config.task_dir_path())?;
+
+        // Sort tasks if sort options are provided
+        if let Some(sort_options) = sort_options {
+            sort_tasks(&mut tasks, sort_options);
+        };
+
+        Ok(tasks)
+    }
+
+    /// Restore a deleted task from the trash or the git history
+    pub fn restore_task(&self, task_id: &str) -> Result<String> {
+        let (task, changes) =
+            storage::plan_restore_task(&self.path_config.task_dir_path(), task_id)?;
+        self.commit(changes)?;
+
+        log::debug!("Restored task: {}", task.id);
+
+        Ok(task.id)
+    }
+
+    /// Permanently remove tasks from the trash
+    ///
+    /// Only tasks deleted before `older_than` are removed if it is provided.
+    pub fn empty_trash<D: Display>(
+        &self,
+        older_than: Option<DateTime<Local>>,
+        force: bool,
+        display_manager: &D,
+    ) -> Result<usize> {
+        let range = DateRange {
+            from: None,
+            to: older_than,
+        };
+        let tasks = storage::load_trashed_tasks(&self.path_config.task_dir_path())?
+            .into_iter()
+            .filter(|task| {
+                task.deleted_at
+                    .as_ref()
+                    .is_none_or(|deleted_at| Self::is_time_in_range(deleted_at, &range))
+            })
+            .collect::<Vec<_>>();
+
+        let count = tasks.len();
+        if count == 0 {
+            return Ok(0);
+        }
+
+        // Confirm deletion if not forced
+        if !force {
+            let message = format!("Are you sure to permanently delete {count} tasks?");
+            if !display_manager.confirm(&message)? {
+                return Ok(0);
+            }
+        }
+
+        self.commit(storage::plan_purge_task(
+            &self.path_config.task_dir_path(),
+            &tasks
+                .iter()
+                .map(|task| task.id.as_str())
+                .collect::<Vec<_>>(),
+        )?)?;
+
+        Ok(count)
+    }
+
+    /// Get the history of a task, oldest first
+    ///
+    /// Deleted tasks are supported as well, as long as they can be found in
+    /// the trash or the git history.
+    pub fn task_log(&self, task_id: &str) -> Result<Vec<TaskLogEntry>> {
+        let full_id = self.resolve_any_task_id(task_id)?;
+
+        match self.existing_git_repo()? {
+            Some(git_repo) => history::task_log(&git_repo, &full_id),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Get the changes made within the date range, grouped by action and
+    /// scope
+    pub fn activity(&self, range: &DateRange) -> Result<Vec<ActivityGroup>> {
+        let Some(git_repo) = self.existing_git_repo()? else {
+            return Ok(Vec::new());
+        };
+
+        history::activity(&git_repo, range)
+    }
+
+    /// Upgrade task files written by older versions of rutd
+    ///
+    /// The upgraded files are rewritten in a single commit. Returns the number
+    /// of upgraded tasks.
+    pub fn migrate_tasks(&self) -> Result<usize> {
+        let (ids, changes) = storage::plan_migrate_tasks(&self.path_config.task_dir_path())?;
+        if !changes.is_empty() {
+            self.commit(changes)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Plan undoing the most recent changes
+    ///
+    /// Only changes made by rutd can be undone. The returned plan can be
+    /// previewed before being applied with `undo`.
+    pub fn plan_undo(&self, count: usize) -> Result<UndoPlan> {
+        if count == 0 {
+            return Err(anyhow::anyhow!("Number of changes to undo must be positive").into());
+        }
+
+        let git_repo = self
+            .existing_git_repo()?
+            .context("No commits found in the tasks repository")?;
+        if !git_repo.uncommitted_changes()?.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Some changes to tasks are not committed yet, commit them before undoing"
+            )
+            .into());
+        }
+        let commits = git_repo.recent_commits(count)?;
+        let head = commits[0].id;
+        let target = commits[commits.len() - 1].parent;
+
+        let changes = history::task_changes(&git_repo, Some(head), target)?;
+        let active_task = history::find_active_task(&git_repo, &commits)?;
+
+        Ok(UndoPlan {
+            commits,
+            target,
+            changes,
+            active_task,
+        })
+    }
+
+    /// Undo changes according to the plan
+    ///
+    /// Commits that have not been pushed are dropped from the history, while
+    /// pushed ones are reverted with a new commit to keep the remote intact.
+    /// Undoing the initial commit is also recorded with a new commit, as there
+    /// is no commit to go back to.
+    pub fn undo(&self, plan: &UndoPlan) -> Result<()> {
+        let task_dir = self.path_config.task_dir_path();
+        let git_repo = self.git_repo()?;
+
+        // Make sure nothing has changed since the plan was made
+        if git_repo.head_id()? != plan.commits.first().map(|commit| commit.id) {
+            return Err(anyhow::anyhow!("Tasks have changed since the undo was planned").into());
+        }
+
+        // Drop the commits unless they are pushed, or there is no commit to go
+        // back to
+        let reset_target = plan.target.filter(|_| !plan.is_pushed());
+        let ids = plan
+            .changes
+            .iter()
+            .map(|change| change.task().id.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let commit_message = GitRepo::generate_commit_message(
+            "undo",
+            None,
+            None,
+            &format!("Undo {} changes", plan.commits.len()),
+            &ids.join("\n"),
+        );
+
+        if self.dry_run {
+            let head = Some(plan.commits[0].id);
+            let mut changes = ChangeSet {
+                message: match reset_target {
+                    Some(target) => format!("Reset to commit {target}"),
+                    None => commit_message,
+                },
+                ..Default::default()
+            };
+            for path in git_repo.changed_files(head, plan.target)? {
+                changes.files.push(FileChange {
+                    old: git_repo.read_file(head, &path)?,
+                    new: git_repo.read_file(plan.target, &path)?,
+                    path,
+                });
+            }
+            self.pending.borrow_mut().push(changes);
+            return Ok(());
+        }
+
+        match reset_target {
+            Some(target) => git_repo.reset_to(target)?,
+            None => git_repo.revert_to(plan.target, &commit_message)?,
+        }
+        self.changed.set(true);
+
+        // Clear the active task if it is no longer workable
+        let active_task_file = self.path_config.active_task_file_path();
+        if let Some(active) = active_task::load_active_task(&active_task_file)?
+            && !storage::load_task(&task_dir, &active.task_id)
+                .is_ok_and(|task| task.status == TaskStatus::Todo)
+        {
+            active_task::clear_active_task(&active_task_file)?;
+            log::debug!("Cleared active task after undo: {}", active.task_id);
+        }
+
+        // Resume the task that was active before the undone changes
+        if let Some(active) = &plan.active_task
+            && active_task::load_active_task(&active_task_file)?.is_none()
+        {
+            active_task::save_active_task(&active_task_file, active)?;
+            log::debug!("Resumed active task after undo: {}", active.task_id);
+        }
+
+        log::debug!("Undid {} changes", plan.commits.len());
+        Ok(())
+    }
+
     /// Clone a remote repository
     pub fn clone_repo(&self, url: &str) -> Result<()> {
+        if self.dry_run {
+            self.pending.borrow_mut().push(ChangeSet {
+                message: format!(
+                    "Clone {url} into {}",
+                    self.path_config.task_dir_path().display()
+                ),
+                ..Default::default()
+            });
+            return Ok(());
+        }
         GitRepo::clone(self.path_config.task_dir_path(), url, &self.git_config)?;
         Ok(())
     }
 
-    /// Sync with remote repository
-    pub fn sync(&self, prefer: MergeStrategy) -> Result<()> {
-        let git_repo = GitRepo::init(self.path_config.task_dir_path())?;
-        git_repo.sync(prefer, &self.git_config)?;
-        Ok(())
+    /// List the remotes of the tasks repository
+    pub fn remotes(&self) -> Result<Vec<RemoteInfo>> {
+        match self.existing_git_repo()? {
+            Some(git_repo) => git_repo.remotes(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Get the remote branch the tasks are synced with
+    pub fn sync_target(&self) -> Result<SyncTarget> {
+        GitRepo::open(self.path_config.task_dir_path())?.sync_target(&self.git_config)
+    }
+
+    /// Add a remote to the tasks repository
+    ///
+    /// Without a name, the remote configured in `git.remote` or `origin` is
+    /// added. Returns the name of the remote.
+    pub fn add_remote(&self, name: Option<&str>, url: &str) -> Result<String> {
+        let git_repo = self.git_repo()?;
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => git_repo.default_remote(&self.git_config)?,
+        };
+        if self.dry_run {
+            self.pending.borrow_mut().push(ChangeSet {
+                message: format!("Add remote '{name}' with URL {url}"),
+                ..Default::default()
+            });
+        } else {
+            git_repo.add_remote(&name, url)?;
+        }
+        Ok(name)
+    }
+
+    /// Change the URL of a remote of the tasks repository
+    ///
+    /// Without a name, the remote used by default is changed. Returns the
+    /// name of the remote.
+    pub fn set_remote_url(&self, name: Option<&str>, url: &str) -> Result<String> {
+        let git_repo = self.git_repo()?;
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => git_repo.default_remote(&self.git_config)?,
+        };
+        if self.dry_run {
+            self.pending.borrow_mut().push(ChangeSet {
+                message: format!("Set URL of remote '{name}' to {url}"),
+                ..Default::default()
+            });
+        } else {
+            git_repo.set_remote_url(&name, url)?;
+        }
+        Ok(name)
+    }
+
+    /// Commit the changes to tasks that are not committed yet
+    ///
+    /// All pending changes are recorded in a single commit, with the given
+    /// description or a generated one. Returns the IDs of the changed tasks,
+    /// which is empty if there was nothing to commit.
+    pub fn commit_pending_changes(&self, description: Option<&str>) -> Result<Vec<String>> {
+        let git_repo = self.git_repo()?;
+        let mut changes = git_repo.uncommitted_changes()?;
+        if changes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Find the changed tasks, a task moved to the trash is changed twice
+        let mut tasks = Vec::<Task>::new();
+        for file in &changes.files {
+            let Some(Ok((task, _))) = file
+                .new
+                .as_deref()
+                .or(file.old.as_deref())
+                .map(migration::parse_task)
+            else {
+                continue;
+            };
+            if !tasks.iter().any(|t| t.id == task.id) {
+                tasks.push(task);
+            }
+        }
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let description = description.map_or_else(
+            || format!("Commit changes to {} tasks", tasks.len()),
+            String::from,
+        );
+        changes.message = storage::commit_message(&tasks, "commit", &description);
+        if self.dry_run {
+            self.pending.borrow_mut().push(changes);
+        } else {
+            git_repo.commit_changes(&changes.message)?;
+            self.changed.set(true);
+        }
+
+        Ok(tasks.into_iter().map(|task| task.id).collect())
+    }
+
+    /// Sync with remote repository
+    ///
+    /// Pending changes are committed first if they are to be committed on
+    /// sync. Conflicts are resolved through the display in interactive mode.
+    pub fn sync<D: Display>(&self, prefer: MergeStrategy, display_manager: &D) -> Result<()> {
+        if self.git_config.auto_commit == AutoCommit::OnSync {
+            self.commit_pending_changes(None)?;
+        }
+
+        let git_repo = self.git_repo()?;
+        if self.dry_run {
+            if let Some(changes) = git_repo.preview_sync(&self.git_config)? {
+                self.pending.borrow_mut().push(changes);
+            }
+            return Ok(());
+        }
+        git_repo.sync(prefer, &self.git_config, display_manager)?;
+        pending_sync::clear_pending_sync(&self.path_config.pending_sync_file_path())
+    }
+
+    /// Whether tasks were changed by this task manager
+    pub const fn has_changes(&self) -> bool {
+        self.changed.get()
+    }
+
+    /// Sync with the remote repository if `git.auto_sync` asks to
+    ///
+    /// Only tasks changed by this task manager are synced, so reading tasks
+    /// never reaches the remote repository, unless a previous automatic sync
+    /// failed, see [`TaskManager::retry_pending_sync`]. They are synced when
+    /// there are changes to push, or when a previous automatic sync failed.
+    /// In `interval` mode, the last sync must also be older than the
+    /// interval, unless it failed. Conflicts that cannot be merged are left
+    /// untouched for an explicit sync to resolve.
+    ///
+    /// A failed sync is recorded to be retried by the next command and its
+    /// error returned. Returns whether tasks were synced.
+    pub fn auto_sync<D: Display>(&self, display_manager: &D) -> Result<bool> {
+        if self.git_config.auto_sync == AutoSync::Off || self.dry_run || !self.has_changes() {
+            return Ok(false);
+        }
+
+        let pending = pending_sync::load_pending_sync(&self.path_config.pending_sync_file_path())?;
+        let git_repo = self.git_repo()?;
+        let Some(status) = git_repo.sync_status(&self.git_config)? else {
+            return Ok(false);
+        };
+        if status.in_progress {
+            log::debug!("A sync is waiting for conflicts to be resolved, not syncing");
+            return Ok(false);
+        }
+
+        let changed = status.ahead > 0
+            || (self.git_config.auto_commit == AutoCommit::OnSync && status.uncommitted > 0);
+        let due = match self.git_config.auto_sync {
+            AutoSync::Interval => status.last_fetch.is_none_or(|last_fetch| {
+                (Local::now() - last_fetch)
+                    .to_std()
+                    .is_ok_and(|elapsed| elapsed >= self.git_config.sync_interval())
+            }),
+            _ => true,
+        };
+        if pending.is_none() && !(changed && due) {
+            return Ok(false);
+        }
+
+        self.run_auto_sync(&git_repo, display_manager)?;
+        Ok(true)
+    }
+
+    /// Retry an automatic sync that failed, if `git.auto_sync` is not off
+    ///
+    /// Unlike [`TaskManager::auto_sync`], the sync is retried whether tasks
+    /// were changed or not, so that changes left behind by a failed sync do
+    /// not wait for the next change. A failed sync is recorded again and its
+    /// error returned. Returns whether tasks were synced.
+    pub fn retry_pending_sync<D: Display>(&self, display_manager: &D) -> Result<bool> {
+        if self.git_config.auto_sync == AutoSync::Off || self.dry_run {
+            return Ok(false);
+        }
+        if pending_sync::load_pending_sync(&self.path_config.pending_sync_file_path())?.is_none() {
+            return Ok(false);
+        }
+
+        let git_repo = self.git_repo()?;
+        let Some(status) = git_repo.sync_status(&self.git_config)? else {
+            return Ok(false);
+        };
+        if status.in_progress {
+            log::debug!("A sync is waiting for conflicts to be resolved, not syncing");
+            return Ok(false);
+        }
+
+        self.run_auto_sync(&git_repo, display_manager)?;
+        Ok(true)
+    }
+
+    /// Compare the tasks with the remote repository as of the last fetch
+    ///
+    /// Returns `None` if no remote is configured.
+    pub fn sync_status(&self) -> Result<Option<SyncStatus>> {
+        match self.existing_git_repo()? {
+            Some(git_repo) => git_repo.sync_status(&self.git_config),
+            None => Ok(None),
+        }
+    }
+
+    /// List the changes made by rutd that are not pushed yet, most recent
+    /// first
+    pub fn unpushed_changes(&self) -> Result<Vec<ActivityEntry>> {
+        let mut entries = Vec::new();
+        let Some(git_repo) = self.existing_git_repo()? else {
+            return Ok(entries);
+        };
+        for commit in git_repo.unpushed_commits(&self.git_config)? {
+            entries.extend(history::activity_entry(&git_repo, commit)?);
+        }
+        Ok(entries)
+    }
+
+    /// List the tasks left conflicting by an interrupted sync
+    pub fn sync_conflicts(&self) -> Result<Vec<TaskConflict>> {
+        let Some(git_repo) = self.existing_git_repo()? else {
+            return Ok(Vec::new());
+        };
+        Ok(git_repo
+            .conflicted_files()?
+            .iter()
+            .map(TaskConflict::new)
+            .collect())
+    }
+
+    /// Finish an interrupted sync once the conflicts are resolved
+    ///
+    /// Every conflicting task file must have been edited into a valid task
+    /// or deleted. Conflicts in local commits still to be rebased are
+    /// resolved as when syncing.
+    pub fn continue_sync<D: Display>(
+        &self,
+        prefer: MergeStrategy,
+        display_manager: &D,
+    ) -> Result<()> {
+        let git_repo = self.git_repo()?;
+        if !git_repo.is_merging() {
+            return Err(anyhow::anyhow!("No sync in progress").into());
+        }
+        let task_dir = self.path_config.task_dir_path();
+        let conflicts = git_repo.conflicted_files()?;
+
+        // Make sure no conflict is left in the task files
+        let mut paths = Vec::new();
+        let mut files = Vec::new();
+        for conflict in conflicts {
+            let new = match fs::read_to_string(task_dir.join(&conflict.path)) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+            if new
+                .as_deref()
+                .is_some_and(|content| migration::parse_task(content).is_err())
+            {
+                paths.push(conflict.path);
+                continue;
+            }
+            files.push(FileChange {
+                path: conflict.path,
+                old: conflict.ours,
+                new,
+            });
+        }
+        if !paths.is_empty() {
+            return Err(Error::GitConflict { paths });
+        }
+
+        if self.dry_run {
+            self.pending.borrow_mut().push(ChangeSet {
+                message: "Continue sync with remote repository".to_string(),
+                files,
+            });
+            return Ok(());
+        }
+        git_repo.continue_sync(prefer, &self.git_config, display_manager)?;
+        pending_sync::clear_pending_sync(&self.path_config.pending_sync_file_path())
+    }
+
+    /// Abort an interrupted sync, discarding the merged remote changes
+    pub fn abort_sync(&self) -> Result<()> {
+        let git_repo = self.git_repo()?;
+        if !git_repo.is_merging() {
+            return Err(anyhow::anyhow!("No sync in progress").into());
+        }
+        if self.dry_run {
+            let task_dir = self.path_config.task_dir_path();
+            let mut changes = ChangeSet {
+                message: "Abort sync with remote repository".to_string(),
+                ..Default::default()
+            };
+            for conflict in git_repo.conflicted_files()? {
+                changes.files.push(FileChange {
+                    old: fs::read_to_string(task_dir.join(&conflict.path)).ok(),
+                    new: conflict.ours,
+                    path: conflict.path,
+                });
+            }
+            self.pending.borrow_mut().push(changes);
+            return Ok(());
+        }
+        git_repo.abort_sync()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs;
+    use std::{fs, path::Path};
 
     use anyhow::Result;
     use chrono::Local;
@@ -381,15 +1528,16 @@ mod tests {
 
     use super::*;
     use crate::{
-        config::{GitConfig, PathConfig},
+        config::{GitConfig, PathConfig, SyncMode},
         display::Display,
-        task::{Filter, TaskStatus},
+        task::{Filter, Resolution, Side, TaskEvent, TaskStatus},
     };
 
     // Mock display implementation for testing
     struct MockDisplay {
         confirm_result: bool,
         edit_result: Option<String>,
+        resolution: Option<Resolution>,
     }
 
     impl MockDisplay {
@@ -397,6 +1545,7 @@ mod tests {
             Self {
                 confirm_result,
                 edit_result,
+                resolution: None,
             }
         }
     }
@@ -415,6 +1564,10 @@ mod tests {
         fn show_tasks_list(&self, _tasks: &[Task]) {}
         fn show_task_stats(&self, _tasks: &[Task]) {}
         fn show_task_detail(&self, _task: &Task) {}
+
+        fn resolve_conflict(&self, _conflict: &TaskConflict) -> Result<Option<Resolution>> {
+            Ok(self.resolution.clone())
+        }
     }
 
     // Helper function to create a task manager with temporary directories
@@ -434,6 +1587,29 @@ mod tests {
         (task_manager, temp_dir)
     }
 
+    // Helper function to create a bare remote repository and two task
+    // managers with their own tasks directory, returning the URL of the
+    // remote repository along with them
+    fn remote_clones(
+        dir: &Path,
+        git_config: &GitConfig,
+    ) -> Result<(TaskManager, TaskManager, String)> {
+        let remote_dir = dir.join("remote.git");
+        git2::Repository::init_bare(&remote_dir)?;
+        let create_manager = |name: &str| {
+            let path_config = PathConfig {
+                root_dir: dir.join(name),
+                ..Default::default()
+            };
+            TaskManager::new(path_config, git_config.clone())
+        };
+        Ok((
+            create_manager("first"),
+            create_manager("second"),
+            remote_dir.display().to_string(),
+        ))
+    }
+
     #[test]
     fn test_add_task() {
         let (task_manager, _temp_dir) = create_test_task_manager();
@@ -475,10 +1651,13 @@ mod tests {
                 scope: Some("test-scope".to_string()),
                 task_type: Some("test-type".to_string()),
                 status: TaskStatus::Todo,
-                created_at: Local::now().to_rfc3339(),
+                created_at: Local::now().fixed_offset(),
                 updated_at: None,
                 completed_at: None,
                 time_spent: None,
+                deleted_at: None,
+                snoozed_until: None,
+                annotations: Vec::new(),
             };
 
             let file_path = task_dir.join(format!("{}.toml", task.id));
@@ -502,6 +1681,16 @@ mod tests {
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
+        assert!(task_manager.take_invalid_files().is_empty());
+
+        // Hand-edited tasks that cannot be loaded are reported
+        let invalid_file = task_dir.join("test-4.toml");
+        fs::write(&invalid_file, "id = \"test-4\"\ncreated_at = \"today\"\n").unwrap();
+        let result = task_manager.list_tasks(&Filter::default(), None);
+        assert_eq!(result.unwrap().len(), 3);
+        let invalid_files = task_manager.take_invalid_files();
+        assert_eq!(invalid_files.len(), 1);
+        assert_eq!(invalid_files[0].path, invalid_file);
     }
 
     #[test]
@@ -520,10 +1709,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let file_path = task_dir.join(format!("{}.toml", task.id));
@@ -560,10 +1752,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let file_path = task_dir.join(format!("{}.toml", task.id));
@@ -609,10 +1804,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let file_path = task_dir.join(format!("{}.toml", task.id));
@@ -646,10 +1844,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let file_path = task_dir.join(format!("{}.toml", task.id));
@@ -672,6 +1873,741 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bulk_operations() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_dir = task_manager.path_config.task_dir_path();
+
+        let legacy = Some("legacy".to_string());
+        let first_id = task_manager.add_task("First", Priority::Normal, legacy.clone(), None)?;
+        let second_id = task_manager.add_task("Second", Priority::Normal, legacy.clone(), None)?;
+        let other_id = task_manager.add_task("Other", Priority::Normal, None, None)?;
+        task_manager.start_task(&first_id)?;
+
+        let filter = Filter {
+            task_scope: legacy.clone(),
+            ..Default::default()
+        };
+
+        // Nothing is changed if the user cancels
+        let display = MockDisplay::new(false, None);
+        assert!(
+            task_manager
+                .finish_tasks(&[], &filter, false, &display)?
+                .is_empty()
+        );
+
+        // Selecting every task is refused
+        assert!(
+            task_manager
+                .finish_tasks(&[], &Filter::default(), true, &display)
+                .is_err()
+        );
+
+        let display = MockDisplay::new(true, None);
+        let mut finished = task_manager.finish_tasks(&[], &filter, false, &display)?;
+        finished.sort();
+        let mut expected = vec![first_id.clone(), second_id.clone()];
+        expected.sort();
+        assert_eq!(finished, expected);
+
+        // The active task is stopped along the way
+        let active_task_file = task_manager.path_config.active_task_file_path();
+        assert!(active_task::load_active_task(&active_task_file)?.is_none());
+        let first = storage::load_task(&task_dir, &first_id)?;
+        assert_eq!(first.status, TaskStatus::Done);
+        assert!(first.time_spent.is_some());
+
+        // All tasks are recorded in a single commit
+        let git_repo = GitRepo::init(&task_dir)?;
+        let commit = &git_repo.recent_commits(1)?[0];
+        let message = GitRepo::parse_commit_message(&commit.message).unwrap();
+        assert_eq!(message.action, "finish");
+        assert_eq!(message.scope, legacy);
+        assert_eq!(message.task_ids.len(), 2);
+
+        // Tasks that are already done are not selected again
+        assert!(
+            task_manager
+                .abort_tasks(&[], &filter, true, &display)?
+                .is_empty()
+        );
+
+        // Nothing is saved if any of the tasks cannot change
+        assert!(
+            task_manager
+                .abort_tasks(
+                    &[first_id.clone(), other_id.clone()],
+                    &Filter::default(),
+                    true,
+                    &display
+                )
+                .is_err()
+        );
+        let other = storage::load_task(&task_dir, &other_id)?;
+        assert_eq!(other.status, TaskStatus::Todo);
+
+        let modification = Modification {
+            priority: Some(Priority::High),
+            scope: Some("new".to_string()),
+            task_type: None,
+        };
+        let modified = task_manager.modify_tasks(
+            &[first_id.clone(), other_id.clone()],
+            &Filter::default(),
+            &modification,
+            true,
+            &display,
+        )?;
+        assert_eq!(modified.len(), 2);
+        let other = storage::load_task(&task_dir, &other_id)?;
+        assert_eq!(other.priority, Priority::High);
+        assert_eq!(other.scope, Some("new".to_string()));
+
+        // A modification is required
+        assert!(
+            task_manager
+                .modify_tasks(
+                    &[other_id],
+                    &Filter::default(),
+                    &Modification::default(),
+                    true,
+                    &display
+                )
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snooze_and_annotate_tasks() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_dir = task_manager.path_config.task_dir_path();
+        let display = MockDisplay::new(true, None);
+
+        let legacy = Some("legacy".to_string());
+        let first_id = task_manager.add_task("First", Priority::Normal, legacy.clone(), None)?;
+        let second_id = task_manager.add_task("Second", Priority::Normal, legacy.clone(), None)?;
+        let filter = Filter {
+            task_scope: legacy,
+            ..Default::default()
+        };
+
+        // Snoozed tasks are only matched when asked for
+        let until = Local::now().fixed_offset() + chrono::Duration::days(1);
+        let snoozed = task_manager.snooze_tasks(&[], &filter, Some(until), false, &display)?;
+        assert_eq!(snoozed.len(), 2);
+        assert!(storage::load_task(&task_dir, &first_id)?.is_snoozed());
+        let hidden = Filter {
+            snoozed: Some(false),
+            ..Default::default()
+        };
+        assert!(task_manager.list_tasks(&hidden, None)?.is_empty());
+
+        // Until they are unsnoozed
+        task_manager.snooze_tasks(
+            std::slice::from_ref(&first_id),
+            &Filter::default(),
+            None,
+            false,
+            &display,
+        )?;
+        let shown = task_manager.list_tasks(&hidden, None)?;
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].id, first_id);
+
+        // Annotations are added to every selected task in a single commit
+        let annotated = task_manager.annotate_tasks(&[], &filter, " Blocked ", false, &display)?;
+        assert_eq!(annotated.len(), 2);
+        let second = storage::load_task(&task_dir, &second_id)?;
+        assert_eq!(second.annotations.len(), 1);
+        assert_eq!(second.annotations[0].text, "Blocked");
+        let git_repo = GitRepo::init(&task_dir)?;
+        let commit = &git_repo.recent_commits(1)?[0];
+        let message = GitRepo::parse_commit_message(&commit.message).unwrap();
+        assert_eq!(message.task_ids.len(), 2);
+
+        // An annotation is required
+        assert!(
+            task_manager
+                .annotate_tasks(&[second_id], &Filter::default(), " ", true, &display)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_dir = task_manager.path_config.task_dir_path();
+        let scope = Some("work".to_string());
+        let task_id = task_manager.add_task("Task", Priority::Normal, scope.clone(), None)?;
+        let git_repo = GitRepo::init(&task_dir)?;
+        let head = git_repo.head_id()?;
+
+        // A failing step rolls back the files written so far
+        let result = task_manager.transaction("update", "Failing", |manager| {
+            manager.add_task("New task", Priority::High, None, None)?;
+            manager.abort_task(Some(&task_id))?;
+            manager.finish_task(Some("missing"))?;
+            Ok(())
+        });
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        assert_eq!(git_repo.head_id()?, head);
+        assert_eq!(task_manager.list_tasks(&Filter::default(), None)?.len(), 1);
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.status,
+            TaskStatus::Todo
+        );
+
+        // All changes are committed at once
+        let new_id = task_manager.transaction("update", "Reorganize tasks", |manager| {
+            let new_id = manager.add_task("New task", Priority::High, scope.clone(), None)?;
+            manager.abort_task(Some(&task_id))?;
+            Ok(new_id)
+        })?;
+        let commit = &git_repo.recent_commits(1)?[0];
+        assert_eq!(commit.parent, head);
+        let message = GitRepo::parse_commit_message(&commit.message).unwrap();
+        assert_eq!(message.action, "update");
+        assert_eq!(message.scope, scope);
+        assert_eq!(message.description, "Reorganize tasks");
+        assert_eq!(message.task_ids, vec![new_id.clone(), task_id.clone()]);
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.status,
+            TaskStatus::Aborted
+        );
+        assert!(storage::load_task(&task_dir, &new_id).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_pending_changes() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path_config = PathConfig {
+            root_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let git_config = GitConfig {
+            auto_commit: AutoCommit::Never,
+            ..Default::default()
+        };
+        let task_manager = TaskManager::new(path_config, git_config);
+        let git_repo = GitRepo::init(task_manager.path_config.task_dir_path())?;
+        git_repo.commit_changes("Initial commit")?;
+        let head = git_repo.head_id()?;
+
+        // Changes are written but not committed
+        let work = Some("work".to_string());
+        let first_id = task_manager.add_task("First", Priority::Normal, work.clone(), None)?;
+        let second_id = task_manager.add_task("Second", Priority::Normal, work.clone(), None)?;
+        task_manager.finish_task(Some(&first_id))?;
+        assert_eq!(git_repo.head_id()?, head);
+        assert_eq!(task_manager.list_tasks(&Filter::default(), None)?.len(), 2);
+
+        let mut ids = task_manager.commit_pending_changes(None)?;
+        ids.sort();
+        let mut expected = vec![first_id.clone(), second_id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let commit = &git_repo.recent_commits(1)?[0];
+        let message = GitRepo::parse_commit_message(&commit.message).unwrap();
+        assert_eq!(message.action, "commit");
+        assert_eq!(message.scope, work);
+        assert_eq!(message.description, "Commit changes to 2 tasks");
+        assert_eq!(message.task_ids, ids);
+
+        // Nothing is left to commit
+        assert!(task_manager.commit_pending_changes(None)?.is_empty());
+
+        // Undoing is refused while changes are pending
+        task_manager.clean_tasks(
+            &Filter {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            },
+            true,
+            &MockDisplay::new(true, None),
+        )?;
+        assert!(task_manager.plan_undo(1).is_err());
+        let ids = task_manager.commit_pending_changes(Some("Clean up"))?;
+        assert_eq!(ids, vec![first_id]);
+        assert_eq!(
+            git_repo.recent_commits(1)?[0].summary(),
+            "commit(work|-): Clean up"
+        );
+        assert!(task_manager.plan_undo(1).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remote_sync() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let git_config = GitConfig {
+            branch: "tasks".to_string(),
+            ..Default::default()
+        };
+        let (first, second, url) = remote_clones(temp_dir.path(), &git_config)?;
+        let display = MockDisplay::new(true, None);
+
+        // Attach a remote to tasks started locally
+        let first_id = first.add_task("First", Priority::Normal, None, None)?;
+        assert_eq!(
+            first.add_remote(None, "https://example.com/tasks.git")?,
+            "origin"
+        );
+        assert!(first.add_remote(None, &url).is_err());
+        assert_eq!(first.set_remote_url(None, &url)?, "origin");
+        assert_eq!(first.remotes()?[0].url.as_deref(), Some(url.as_str()));
+        assert_eq!(first.sync_target()?.remote_branch, "tasks");
+        first.sync(MergeStrategy::None, &display)?;
+
+        // Tasks are pushed to the configured branch
+        second.clone_repo(&url)?;
+        assert!(second.list_tasks(&Filter::default(), None)?[0].id == first_id);
+        let second_id = second.add_task("Second", Priority::Normal, None, None)?;
+        second.sync(MergeStrategy::None, &display)?;
+
+        first.sync(MergeStrategy::None, &display)?;
+        let mut ids = first
+            .list_tasks(&Filter::default(), None)?
+            .into_iter()
+            .map(|task| task.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        let mut expected = vec![first_id, second_id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        // A dry run compares with the remote as of the last fetch
+        second.add_task("Third", Priority::Normal, None, None)?;
+        second.sync(MergeStrategy::None, &display)?;
+        let dry_run = TaskManager::new(first.path_config.clone(), git_config).with_dry_run(true);
+        dry_run.sync(MergeStrategy::None, &display)?;
+        let changes = dry_run.take_pending_changes();
+        assert!(changes[0].message.contains("is up to date with"));
+        assert!(changes[0].files.is_empty());
+        assert_eq!(first.list_tasks(&Filter::default(), None)?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_history_without_repository() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_dir = task_manager.path_config.task_dir_path();
+
+        // Reading the history never creates the tasks repository
+        assert!(task_manager.activity(&DateRange::default())?.is_empty());
+        assert!(task_manager.sync_status()?.is_none());
+        assert!(task_manager.unpushed_changes()?.is_empty());
+        assert!(task_manager.remotes()?.is_empty());
+        assert!(task_manager.plan_undo(1).is_err());
+        assert!(!task_dir.join(".git").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_merges_task_fields() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let (first, second, url) = remote_clones(temp_dir.path(), &GitConfig::default())?;
+        let display = MockDisplay::new(true, None);
+
+        let task_id = first.add_task("Task", Priority::Normal, None, None)?;
+        first.add_remote(None, &url)?;
+        first.sync(MergeStrategy::None, &display)?;
+        second.clone_repo(&url)?;
+
+        // Change different fields of the same task on both sides
+        let task_dir = first.path_config.task_dir_path();
+        let mut task = storage::load_task(&task_dir, &task_id)?;
+        task.priority = Priority::High;
+        task.updated_at = Some(task.created_at + chrono::Duration::minutes(1));
+        first.save_task(&task, "update", "Raise priority")?;
+        first.sync(MergeStrategy::None, &display)?;
+
+        let task_dir = second.path_config.task_dir_path();
+        let mut task = storage::load_task(&task_dir, &task_id)?;
+        task.time_spent = Some(30);
+        task.updated_at = Some(task.created_at + chrono::Duration::minutes(2));
+        second.save_task(&task, "update", "Add time")?;
+        second.sync(MergeStrategy::None, &display)?;
+
+        // Both changes are kept
+        let task = storage::load_task(&task_dir, &task_id)?;
+        assert_eq!(task.priority, Priority::High);
+        assert_eq!(task.time_spent, Some(30));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_continue_and_abort() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let (first, second, url) = remote_clones(temp_dir.path(), &GitConfig::default())?;
+        let display = MockDisplay::new(true, None);
+
+        let task_id = first.add_task("Task", Priority::Normal, None, None)?;
+        first.add_remote(None, &url)?;
+        first.sync(MergeStrategy::None, &display)?;
+        second.clone_repo(&url)?;
+        assert!(second.continue_sync(MergeStrategy::None, &display).is_err());
+        assert!(second.abort_sync().is_err());
+
+        // Change the same field at the same time on both sides
+        let change_priority = |manager: &TaskManager, priority| -> Result<()> {
+            let task_dir = manager.path_config.task_dir_path();
+            let mut task = storage::load_task(&task_dir, &task_id)?;
+            task.priority = priority;
+            task.updated_at = Some(task.created_at + chrono::Duration::minutes(1));
+            manager.save_task(&task, "update", "Change priority")?;
+            Ok(())
+        };
+        change_priority(&first, Priority::High)?;
+        first.sync(MergeStrategy::None, &display)?;
+        change_priority(&second, Priority::Low)?;
+
+        let err = second.sync(MergeStrategy::None, &display).unwrap_err();
+        assert!(matches!(err.root(), Error::GitConflict { .. }));
+        let conflicts = second.sync_conflicts()?;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].description(), Some("Task"));
+        assert_eq!(conflicts[0].fields[0].field, "priority");
+        assert!(second.sync(MergeStrategy::None, &display).is_err());
+
+        // Aborting goes back to the local version
+        second.abort_sync()?;
+        let task_dir = second.path_config.task_dir_path();
+        assert!(second.sync_conflicts()?.is_empty());
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.priority,
+            Priority::Low
+        );
+
+        // Conflicts must be resolved before continuing
+        assert!(second.sync(MergeStrategy::None, &display).is_err());
+        assert!(second.continue_sync(MergeStrategy::None, &display).is_err());
+        let task_file = task_dir.join(conflicts[0].path());
+        let mut task = conflicts[0].theirs.clone().unwrap();
+        task.priority = Priority::Urgent;
+        fs::write(&task_file, migration::serialize_task(&task)?)?;
+        second.continue_sync(MergeStrategy::None, &display)?;
+        assert!(second.sync_conflicts()?.is_empty());
+
+        // The resolved task is pushed
+        first.sync(MergeStrategy::None, &display)?;
+        let task_dir = first.path_config.task_dir_path();
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.priority,
+            Priority::Urgent
+        );
+
+        // Conflicts can be resolved interactively
+        change_priority(&first, Priority::Low)?;
+        first.sync(MergeStrategy::None, &display)?;
+        change_priority(&second, Priority::High)?;
+        let display = MockDisplay {
+            resolution: Some(Resolution::Fields(vec![Side::Theirs])),
+            ..MockDisplay::new(true, None)
+        };
+        second.sync(MergeStrategy::Interactive, &display)?;
+        let task_dir = second.path_config.task_dir_path();
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.priority,
+            Priority::Low
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebase_sync() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let git_config = GitConfig {
+            sync_mode: SyncMode::Rebase,
+            ..Default::default()
+        };
+        let (first, second, url) = remote_clones(temp_dir.path(), &git_config)?;
+        let display = MockDisplay::new(true, None);
+
+        let task_id = first.add_task("Task", Priority::Normal, None, None)?;
+        first.add_remote(None, &url)?;
+        first.sync(MergeStrategy::None, &display)?;
+        second.clone_repo(&url)?;
+
+        // Local commits are replayed on top of the remote ones
+        first.add_task("First", Priority::Normal, None, None)?;
+        first.sync(MergeStrategy::None, &display)?;
+        second.add_task("Second", Priority::Normal, None, None)?;
+        second.sync(MergeStrategy::None, &display)?;
+        let git_repo = GitRepo::init(second.path_config.task_dir_path())?;
+        let head = git_repo.get_repo().head()?.peel_to_commit()?;
+        assert_eq!(head.parent_count(), 1);
+        assert!(head.message_bytes().starts_with(b"create"));
+        assert_eq!(second.list_tasks(&Filter::default(), None)?.len(), 3);
+
+        // Replayed commits keep their original author
+        let alice = TaskManager::new(
+            second.path_config.clone(),
+            GitConfig {
+                sync_mode: SyncMode::Rebase,
+                author_name: "Alice".to_string(),
+                author_email: "alice@example.com".to_string(),
+                ..Default::default()
+            },
+        );
+        first.sync(MergeStrategy::None, &display)?;
+        first.add_task("Remote", Priority::Normal, None, None)?;
+        first.sync(MergeStrategy::None, &display)?;
+        alice.add_task("Alice's task", Priority::Normal, None, None)?;
+        second.sync(MergeStrategy::None, &display)?;
+        let head = git_repo.get_repo().head()?.peel_to_commit()?;
+        assert!(head.message_bytes().starts_with(b"create"));
+        assert_eq!(head.author().name().ok(), Some("Alice"));
+        assert_eq!(head.author().email().ok(), Some("alice@example.com"));
+        assert_eq!(
+            head.committer().name().ok(),
+            Some(crate::git::repo::COMMITTER_NAME)
+        );
+
+        // Conflicts stop the rebase until resolved
+        first.sync(MergeStrategy::None, &display)?;
+        let change_priority = |manager: &TaskManager, priority| -> Result<()> {
+            let task_dir = manager.path_config.task_dir_path();
+            let mut task = storage::load_task(&task_dir, &task_id)?;
+            task.priority = priority;
+            task.updated_at = Some(task.created_at + chrono::Duration::minutes(1));
+            manager.save_task(&task, "update", "Change priority")?;
+            Ok(())
+        };
+        change_priority(&first, Priority::High)?;
+        first.sync(MergeStrategy::None, &display)?;
+        change_priority(&second, Priority::Low)?;
+        assert!(second.sync(MergeStrategy::None, &display).is_err());
+        let conflicts = second.sync_conflicts()?;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ours.as_ref().unwrap().priority, Priority::Low);
+        assert_eq!(
+            conflicts[0].theirs.as_ref().unwrap().priority,
+            Priority::High
+        );
+
+        // Aborting restores the local commits
+        second.abort_sync()?;
+        let task_dir = second.path_config.task_dir_path();
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.priority,
+            Priority::Low
+        );
+
+        // Keeping the local version replays it on top of the remote one
+        second.sync(MergeStrategy::Local, &display)?;
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.priority,
+            Priority::Low
+        );
+        let head = git_repo.get_repo().head()?.peel_to_commit()?;
+        assert_eq!(head.parent_count(), 1);
+        assert!(head.message_bytes().starts_with(b"update"));
+
+        // The rebase goes on once conflicts are resolved
+        first.sync(MergeStrategy::None, &display)?;
+        change_priority(&first, Priority::Urgent)?;
+        first.sync(MergeStrategy::None, &display)?;
+        change_priority(&second, Priority::Normal)?;
+        assert!(second.sync(MergeStrategy::None, &display).is_err());
+        let mut task = second.sync_conflicts()?[0].theirs.clone().unwrap();
+        task.description = "Resolved".to_string();
+        fs::write(
+            task_dir.join(format!("{task_id}.toml")),
+            migration::serialize_task(&task)?,
+        )?;
+        second.continue_sync(MergeStrategy::None, &display)?;
+        assert!(!git_repo.is_merging());
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.description,
+            "Resolved"
+        );
+        let head = git_repo.get_repo().head()?.peel_to_commit()?;
+        assert_eq!(head.parent_count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_status() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let (task_manager, _, url) = remote_clones(temp_dir.path(), &GitConfig::default())?;
+        let display = MockDisplay::new(true, None);
+
+        // Nothing to report without a remote
+        let task_id = task_manager.add_task("Task", Priority::Normal, None, None)?;
+        assert!(task_manager.sync_status()?.is_none());
+
+        // Nothing is pushed before the first sync
+        task_manager.add_remote(None, &url)?;
+        let status = task_manager.sync_status()?.unwrap();
+        assert!(!status.tracked);
+        assert_eq!((status.ahead, status.behind), (1, 0));
+        assert!(status.last_fetch.is_none());
+
+        task_manager.sync(MergeStrategy::None, &display)?;
+        let status = task_manager.sync_status()?.unwrap();
+        assert!(status.tracked);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+        assert!(status.last_fetch.is_some());
+        assert!(task_manager.unpushed_changes()?.is_empty());
+
+        // Local changes are listed with their tasks
+        task_manager.start_task(&task_id)?;
+        task_manager.stop_task()?;
+        task_manager.add_task("Another task", Priority::Normal, None, None)?;
+        let status = task_manager.sync_status()?.unwrap();
+        assert_eq!((status.ahead, status.behind), (2, 0));
+        assert_eq!(status.uncommitted, 0);
+        let changes = task_manager.unpushed_changes()?;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].message.action, "create");
+        assert_eq!(changes[1].message.action, "update");
+        assert_eq!(changes[1].tasks[0].description.as_deref(), Some("Task"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_sync() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let git_config = GitConfig {
+            auto_sync: AutoSync::OnWrite,
+            ..Default::default()
+        };
+        let (task_manager, _, url) = remote_clones(temp_dir.path(), &git_config)?;
+        let display = MockDisplay::new(true, None);
+        let path_config = task_manager.path_config.clone();
+        let pending_file = path_config.pending_sync_file_path();
+
+        // Nothing to sync with without a remote
+        task_manager.add_task("Task", Priority::Normal, None, None)?;
+        assert!(!task_manager.auto_sync(&display)?);
+
+        // Changes are pushed
+        task_manager.add_remote(None, &url)?;
+        assert!(task_manager.auto_sync(&display)?);
+        assert_eq!(task_manager.sync_status()?.unwrap().ahead, 0);
+        assert!(!task_manager.auto_sync(&display)?);
+
+        // A failed sync is recorded and retried
+        task_manager.add_task("Another task", Priority::Normal, None, None)?;
+        let missing = temp_dir.path().join("missing.git").display().to_string();
+        task_manager.set_remote_url(None, &missing)?;
+        assert!(task_manager.auto_sync(&display).is_err());
+        assert!(pending_sync::load_pending_sync(&pending_file)?.is_some());
+        task_manager.set_remote_url(None, &url)?;
+
+        // Reading tasks never syncs changes to push
+        let reader = TaskManager::new(
+            path_config.clone(),
+            GitConfig {
+                auto_sync: AutoSync::OnWrite,
+                ..Default::default()
+            },
+        );
+        reader.list_tasks(&Filter::default(), None)?;
+        assert!(!reader.has_changes());
+        assert!(!reader.auto_sync(&display)?);
+        assert_eq!(reader.sync_status()?.unwrap().ahead, 1);
+
+        // But retries a failed sync
+        assert!(reader.retry_pending_sync(&display)?);
+        assert!(pending_sync::load_pending_sync(&pending_file)?.is_none());
+        assert_eq!(reader.sync_status()?.unwrap().ahead, 0);
+        assert!(!reader.retry_pending_sync(&display)?);
+
+        // Only once per interval
+        let task_manager = TaskManager::new(
+            path_config,
+            GitConfig {
+                auto_sync: AutoSync::Interval,
+                ..Default::default()
+            },
+        );
+        task_manager.add_task("Third task", Priority::Normal, None, None)?;
+        assert!(!task_manager.auto_sync(&display)?);
+        assert_eq!(task_manager.sync_status()?.unwrap().ahead, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_dir = task_manager.path_config.task_dir_path();
+        let task_id = task_manager.add_task("Task", Priority::Normal, None, None)?;
+        let git_repo = GitRepo::init(&task_dir)?;
+        let head = git_repo.head_id()?;
+
+        let dry_run = TaskManager::new(task_manager.path_config.clone(), GitConfig::default())
+            .with_dry_run(true);
+        let new_id = dry_run.add_task("New task", Priority::High, None, None)?;
+        dry_run.start_task(&task_id)?;
+        dry_run.finish_task(Some(&task_id))?;
+
+        // Nothing is written or committed
+        assert_eq!(git_repo.head_id()?, head);
+        assert!(storage::load_task(&task_dir, &new_id).is_err());
+        assert_eq!(
+            storage::load_task(&task_dir, &task_id)?.status,
+            TaskStatus::Todo
+        );
+        let active_task_file = task_manager.path_config.active_task_file_path();
+        assert!(active_task::load_active_task(&active_task_file)?.is_none());
+
+        // But every change is recorded
+        let changes = dry_run.take_pending_changes();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].message.starts_with("create"));
+        assert_eq!(changes[0].files[0].old, None);
+        assert!(changes[1].message.starts_with("finish"));
+        assert!(changes[1].files[0].old.is_some());
+        assert!(dry_run.take_pending_changes().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_chained_changes() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_id = task_manager.add_task("Task", Priority::Normal, None, None)?;
+        task_manager.start_task(&task_id)?;
+
+        // Finishing the active task sees the time saved by stopping it first
+        let dry_run = TaskManager::new(task_manager.path_config.clone(), GitConfig::default())
+            .with_dry_run(true);
+        dry_run.finish_task(Some(&task_id))?;
+        let changes = dry_run.take_pending_changes();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[1].message.starts_with("finish"));
+        assert_eq!(changes[1].files[0].old, changes[0].files[0].new);
+        let (task, _) = migration::parse_task(changes[1].files[0].new.as_deref().unwrap())?;
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.time_spent.is_some());
+
+        // No repository is created by a dry run
+        let (task_manager, _temp_dir) = create_test_task_manager();
+        let task_dir = task_manager.path_config.task_dir_path();
+        let dry_run = task_manager.with_dry_run(true);
+        assert!(dry_run.plan_undo(1).is_err());
+        assert!(!task_dir.join(".git").exists());
+
+        Ok(())
+    }
+
     #[test]
     fn test_clean_tasks() {
         let (task_manager, _temp_dir) = create_test_task_manager();
@@ -688,10 +2624,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Done,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
-            completed_at: Some(Local::now().to_rfc3339()),
+            completed_at: Some(Local::now().fixed_offset()),
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let todo_task = Task {
@@ -701,10 +2640,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         fs::write(
@@ -739,6 +2681,328 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_trash_and_restore_tasks() {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+
+        let task_id = task_manager
+            .add_task("Trash me", Priority::Normal, None, None)
+            .unwrap();
+        let task_file = task_manager
+            .path_config
+            .task_dir_path()
+            .join(format!("{task_id}.toml"));
+        let display = MockDisplay::new(true, None);
+
+        // Cleaning moves the task to the trash
+        let count = task_manager
+            .clean_tasks(&Filter::default(), true, &display)
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(!task_file.exists());
+        let trashed = task_manager.list_trash(None).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert!(trashed[0].deleted_at.is_some());
+
+        // Restoring moves it back
+        assert_eq!(task_manager.restore_task(&task_id[..8]).unwrap(), task_id);
+        assert!(task_file.exists());
+        assert!(task_manager.list_trash(None).unwrap().is_empty());
+
+        // Trash newer than the cutoff is kept
+        task_manager
+            .clean_tasks(&Filter::default(), true, &display)
+            .unwrap();
+        let cutoff = Local::now() - chrono::Duration::days(30);
+        let count = task_manager
+            .empty_trash(Some(cutoff), true, &display)
+            .unwrap();
+        assert_eq!(count, 0);
+
+        // Emptying the trash removes it for good, but it stays in the history
+        let count = task_manager.empty_trash(None, true, &display).unwrap();
+        assert_eq!(count, 1);
+        assert!(task_manager.list_trash(None).unwrap().is_empty());
+        assert_eq!(task_manager.restore_task(&task_id).unwrap(), task_id);
+        let restored: Task = toml::from_str(&fs::read_to_string(&task_file).unwrap()).unwrap();
+        assert_eq!(restored.description, "Trash me");
+        assert!(restored.deleted_at.is_none());
+    }
+
+    #[test]
+    fn test_short_task_numbers() -> Result<()> {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+
+        let first_id = task_manager.add_task("First task", Priority::Normal, None, None)?;
+        let second_id = task_manager.add_task("Second task", Priority::Normal, None, None)?;
+
+        // Tasks are numbered on first use
+        let working_set = task_manager.working_set()?;
+        assert_eq!(working_set.tasks.len(), 2);
+        let first = working_set.number(&first_id).unwrap();
+        let second = working_set.number(&second_id).unwrap();
+
+        // Numbers are accepted in place of IDs
+        assert_eq!(
+            task_manager.finish_task(Some(&first.to_string()))?,
+            first_id
+        );
+
+        // Numbers are kept until the working set is refreshed
+        assert_eq!(task_manager.working_set()?, working_set);
+        assert_eq!(task_manager.start_task(&second.to_string())?, second_id);
+        task_manager.stop_task()?;
+
+        let working_set = task_manager.refresh_working_set()?;
+        assert_eq!(working_set.tasks, vec![second_id.clone()]);
+        assert_eq!(task_manager.abort_task(Some("1"))?, second_id);
+
+        // Numbers out of the working set never match an ID prefix
+        let third_id = task_manager.add_task("Third task", Priority::Normal, None, None)?;
+        task_manager.refresh_working_set()?;
+        // Use the first digit of its ID if any, 1 being its number
+        let number = Some(&third_id[..1])
+            .filter(|digit| *digit != "1" && digit.bytes().all(|b| b.is_ascii_digit()))
+            .unwrap_or("7");
+        let err = task_manager.finish_task(Some(number)).unwrap_err();
+        assert!(matches!(err.root(), Error::NotFound(_)));
+        assert_eq!(
+            storage::load_task(&task_manager.path_config.task_dir_path(), &third_id)?.status,
+            TaskStatus::Todo
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_task_ids() {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+
+        let first_id = task_manager
+            .add_task("First task", Priority::Normal, None, None)
+            .unwrap();
+        let second_id = task_manager
+            .add_task("Second task", Priority::Normal, None, None)
+            .unwrap();
+
+        // Prefixes matching several tasks are ambiguous
+        let err = task_manager.resolve_task_id("").unwrap_err();
+        let Error::AmbiguousId { candidates, .. } = err.root() else {
+            panic!("Expected an ambiguous ID, got {err}");
+        };
+        let mut ids = task_manager
+            .candidate_tasks(candidates)
+            .unwrap()
+            .into_iter()
+            .map(|task| task.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        let mut expected = vec![first_id.clone(), second_id.clone()];
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert_eq!(
+            task_manager.resolve_task_id(&first_id[..8]).unwrap(),
+            first_id
+        );
+
+        // Deleted tasks are found in the trash, then in the history
+        let display = MockDisplay::new(true, None);
+        task_manager
+            .clean_tasks(&Filter::default(), true, &display)
+            .unwrap();
+        assert!(matches!(
+            task_manager.resolve_task_id(&first_id[..8]),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            task_manager.resolve_deleted_task_id("").unwrap_err().root(),
+            Error::AmbiguousId { .. }
+        ));
+        assert_eq!(
+            task_manager
+                .resolve_deleted_task_id(&first_id[..8])
+                .unwrap(),
+            first_id
+        );
+        assert_eq!(
+            task_manager.resolve_any_task_id(&second_id[..8]).unwrap(),
+            second_id
+        );
+
+        task_manager.empty_trash(None, true, &display).unwrap();
+        assert_eq!(
+            task_manager
+                .resolve_deleted_task_id(&first_id[..8])
+                .unwrap(),
+            first_id
+        );
+        assert!(matches!(
+            task_manager.resolve_any_task_id("nonexistent"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_task_log() {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+
+        let task_id = task_manager
+            .add_task("Logged task", Priority::Normal, None, None)
+            .unwrap();
+        task_manager.start_task(&task_id).unwrap();
+        task_manager.finish_task(Some(&task_id)).unwrap();
+        let display = MockDisplay::new(true, None);
+        task_manager
+            .clean_tasks(&Filter::default(), true, &display)
+            .unwrap();
+        task_manager.empty_trash(None, true, &display).unwrap();
+
+        let log = task_manager.task_log(&task_id[..8]).unwrap();
+        let events = log
+            .iter()
+            .map(|entry| entry.events.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                vec![TaskEvent::Created],
+                vec![TaskEvent::TimeAdded(0)],
+                vec![TaskEvent::Status(TaskStatus::Todo, TaskStatus::Done)],
+                vec![TaskEvent::Deleted],
+                vec![TaskEvent::Purged],
+            ]
+        );
+        assert!(log[0].task.is_some());
+        assert!(log[4].task.is_none());
+
+        assert!(matches!(
+            task_manager.task_log("nonexistent"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_activity() {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+
+        let start = Local::now() - chrono::Duration::seconds(1);
+        let first = task_manager
+            .add_task(
+                "First task",
+                Priority::Normal,
+                Some("work".to_string()),
+                None,
+            )
+            .unwrap();
+        let second = task_manager
+            .add_task("Second task", Priority::Normal, None, None)
+            .unwrap();
+        task_manager.finish_task(Some(&first)).unwrap();
+        let display = MockDisplay::new(true, None);
+        task_manager
+            .clean_tasks(&Filter::default(), true, &display)
+            .unwrap();
+        task_manager.empty_trash(None, true, &display).unwrap();
+
+        let groups = task_manager
+            .activity(&DateRange {
+                from: Some(start),
+                to: None,
+            })
+            .unwrap();
+        let keys = groups
+            .iter()
+            .map(|group| (group.action.as_str(), group.scope.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                ("create", None),
+                ("create", Some("work")),
+                ("delete", None),
+                ("finish", Some("work")),
+                ("purge", None),
+            ]
+        );
+
+        // Descriptions of permanently removed tasks are still found
+        let purged = &groups[4].entries[0].tasks;
+        assert_eq!(purged.len(), 2);
+        assert!(purged.iter().any(|task| task.id == second
+            && task.description.as_deref() == Some("Second task")));
+
+        // Nothing happened in the future
+        let groups = task_manager
+            .activity(&DateRange {
+                from: Some(Local::now() + chrono::Duration::days(1)),
+                to: None,
+            })
+            .unwrap();
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn test_undo() {
+        let (task_manager, _temp_dir) = create_test_task_manager();
+
+        let first_id = task_manager
+            .add_task("First task", Priority::Normal, None, None)
+            .unwrap();
+        let second_id = task_manager
+            .add_task("Second task", Priority::Normal, None, None)
+            .unwrap();
+        task_manager.start_task(&first_id).unwrap();
+        task_manager.finish_task(Some(&first_id)).unwrap();
+        assert!(
+            active_task::load_active_task(&task_manager.path_config.active_task_file_path())
+                .unwrap()
+                .is_none()
+        );
+
+        // Undo finishing the task, which also stopped it
+        let plan = task_manager.plan_undo(2).unwrap();
+        assert_eq!(plan.commits.len(), 2);
+        assert!(!plan.is_pushed());
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].task().id, first_id);
+        assert_eq!(
+            plan.active_task.as_ref().map(|task| task.task_id.as_str()),
+            Some(first_id.as_str())
+        );
+        task_manager.undo(&plan).unwrap();
+
+        let task =
+            storage::load_task(&task_manager.path_config.task_dir_path(), &first_id).unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+        let active =
+            active_task::load_active_task(&task_manager.path_config.active_task_file_path())
+                .unwrap()
+                .unwrap();
+        assert_eq!(active.task_id, first_id);
+
+        // Undo creating the second task
+        let plan = task_manager.plan_undo(1).unwrap();
+        assert!(plan.active_task.is_none());
+        task_manager.undo(&plan).unwrap();
+        assert!(storage::load_task(&task_manager.path_config.task_dir_path(), &second_id).is_err());
+
+        // Undo creating the first task, which is the initial commit
+        let plan = task_manager.plan_undo(1).unwrap();
+        assert!(plan.target.is_none());
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].task().id, first_id);
+        task_manager.undo(&plan).unwrap();
+        assert!(storage::load_task(&task_manager.path_config.task_dir_path(), &first_id).is_err());
+        assert!(task_manager.active_task().unwrap().is_none());
+
+        // Which is recorded with a new commit
+        let git_repo = GitRepo::init(task_manager.path_config.task_dir_path()).unwrap();
+        let commits = git_repo.recent_commits(2).unwrap();
+        assert!(commits[0].message.starts_with("undo"));
+        assert!(task_manager.plan_undo(3).is_err());
+        assert!(task_manager.plan_undo(0).is_err());
+    }
+
     #[test]
     fn test_edge_cases_for_task_status_changes() {
         let (task_manager, _temp_dir) = create_test_task_manager();
@@ -755,10 +3019,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let done_task = Task {
@@ -768,10 +3035,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Done,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
-            completed_at: Some(Local::now().to_rfc3339()),
+            completed_at: Some(Local::now().fixed_offset()),
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let aborted_task = Task {
@@ -781,10 +3051,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Aborted,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
-            completed_at: Some(Local::now().to_rfc3339()),
+            completed_at: Some(Local::now().fixed_offset()),
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         // Save all tasks
@@ -859,10 +3132,13 @@ mod tests {
             scope: Some("test-scope".to_string()),
             task_type: Some("feature".to_string()),
             status: TaskStatus::Todo,
-            created_at: "2023-05-15T12:00:00+00:00".to_string(),
-            updated_at: Some("2023-05-16T14:30:00+00:00".to_string()),
+            created_at: DateTime::parse_from_rfc3339("2023-05-15T12:00:00+00:00").unwrap(),
+            updated_at: Some(DateTime::parse_from_rfc3339("2023-05-16T14:30:00+00:00").unwrap()),
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         // Test 1: Empty filter should match
@@ -973,10 +3249,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let file_path = task_dir.join(format!("{}.toml", task.id));
@@ -995,10 +3274,13 @@ mod tests {
             scope: None,
             task_type: None,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         let second_file_path = task_dir.join(format!("{}.toml", second_task.id));
@@ -1058,10 +3340,10 @@ mod tests {
     fn test_is_time_in_range() {
         // Test the time range functionality directly
 
-        // Setup time strings for testing
-        let time1 = "2023-01-15T12:00:00+00:00";
-        let time2 = "2023-02-15T12:00:00+00:00";
-        let time3 = "2023-03-15T12:00:00+00:00";
+        // Setup times for testing
+        let time1 = &DateTime::parse_from_rfc3339("2023-01-15T12:00:00+00:00").unwrap();
+        let time2 = &DateTime::parse_from_rfc3339("2023-02-15T12:00:00+00:00").unwrap();
+        let time3 = &DateTime::parse_from_rfc3339("2023-03-15T12:00:00+00:00").unwrap();
 
         // Create a date range that includes time2 but not time1 or time3
         let from = DateTime::parse_from_rfc3339("2023-02-01T00:00:00+00:00")
@@ -0,0 +1,568 @@
+use std::{fmt::Debug, path::Path};
+
+use super::{Task, TaskStatus, migration};
+use crate::{
+    Result,
+    git::{MergeStrategy, repo::ConflictedFile},
+};
+
+/// Field of a task changed differently on both sides of a merge
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FieldConflict {
+    /// Name of the field
+    pub field: &'static str,
+    /// Local value of the field
+    pub ours: String,
+    /// Remote value of the field
+    pub theirs: String,
+}
+
+/// Result of merging two versions of a task
+#[derive(Clone, Debug)]
+pub struct TaskMerge {
+    /// Merged task, conflicting fields keep their local value
+    pub task: Task,
+    /// Fields that could not be merged
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// Task file left conflicting by an interrupted sync
+#[derive(Clone, Debug)]
+pub struct TaskConflict {
+    /// Versions of the conflicting file
+    pub file: ConflictedFile,
+    /// Common ancestor of the task, `None` if missing or unreadable
+    pub base: Option<Task>,
+    /// Local version of the task, `None` if deleted or unreadable
+    pub ours: Option<Task>,
+    /// Remote version of the task, `None` if deleted or unreadable
+    pub theirs: Option<Task>,
+    /// Fields changed differently on both sides
+    pub fields: Vec<FieldConflict>,
+}
+
+/// Side of a merge
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Side {
+    /// Local version
+    Ours,
+    /// Remote version
+    Theirs,
+}
+
+/// Resolution of a conflicting task chosen by the user
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Resolution {
+    /// Keep the whole file from one side
+    Side(Side),
+    /// Merge field by field, keeping the given side for each conflicting
+    /// field in order
+    Fields(Vec<Side>),
+    /// Replace the file with the given content
+    Content(String),
+}
+
+impl TaskConflict {
+    /// Compare the versions of a conflicting task file
+    pub fn new(file: &ConflictedFile) -> Self {
+        let parse = |content: &Option<String>| {
+            content
+                .as_deref()
+                .and_then(|content| migration::parse_task(content).ok())
+                .map(|(task, _)| task)
+        };
+        let base = parse(&file.base);
+        let ours = parse(&file.ours);
+        let theirs = parse(&file.theirs);
+        let fields = match (&ours, &theirs) {
+            (Some(ours), Some(theirs)) => {
+                merge_tasks(base.as_ref(), ours, theirs, MergeStrategy::None).conflicts
+            }
+            _ => Vec::new(),
+        };
+
+        Self {
+            file: file.clone(),
+            base,
+            ours,
+            theirs,
+            fields,
+        }
+    }
+
+    /// Path of the task file, relative to the tasks directory
+    pub fn path(&self) -> &Path {
+        &self.file.path
+    }
+
+    /// Description of the conflicting task, preferring the local one
+    pub fn description(&self) -> Option<&str> {
+        self.ours
+            .as_ref()
+            .or(self.theirs.as_ref())
+            .map(|task| task.description.as_str())
+    }
+
+    /// Merge both versions of the task field by field
+    ///
+    /// Conflicting fields take the value of the given side in order, or the
+    /// local one if no side is given. Returns `None` if either version is
+    /// not a task.
+    pub fn merge(&self, sides: &[Side]) -> Option<Task> {
+        let (ours, theirs) = (self.ours.as_ref()?, self.theirs.as_ref()?);
+        let mut task = merge_tasks(self.base.as_ref(), ours, theirs, MergeStrategy::None).task;
+        for (conflict, side) in self.fields.iter().zip(sides) {
+            if *side == Side::Theirs {
+                take_field(&mut task, theirs, conflict.field);
+            }
+        }
+        Some(task)
+    }
+
+    /// Get the content of the task file resolved as chosen by the user
+    ///
+    /// Returns `None` if the file is to be removed.
+    pub fn resolve(&self, resolution: &Resolution) -> Result<Option<String>> {
+        match resolution {
+            Resolution::Side(Side::Ours) => Ok(self.file.ours.clone()),
+            Resolution::Side(Side::Theirs) => Ok(self.file.theirs.clone()),
+            Resolution::Fields(sides) => {
+                let task = self.merge(sides).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Cannot merge {} field by field, both versions must be tasks",
+                        self.path().display()
+                    )
+                })?;
+                Ok(Some(migration::serialize_task(&task)?))
+            }
+            Resolution::Content(content) => {
+                migration::parse_task(content)?;
+                Ok(Some(content.clone()))
+            }
+        }
+    }
+}
+
+/// Replace a field of a task with its value in another version
+fn take_field(task: &mut Task, from: &Task, field: &str) {
+    match field {
+        "description" => task.description = from.description.clone(),
+        "priority" => task.priority = from.priority,
+        "scope" => task.scope = from.scope.clone(),
+        "type" => task.task_type = from.task_type.clone(),
+        "status" => {
+            task.status = from.status;
+            task.completed_at = from.completed_at;
+        }
+        "snoozed until" => task.snoozed_until = from.snoozed_until,
+        _ => {}
+    }
+}
+
+/// Merge a field changed on one or both sides
+///
+/// A field changed on one side only takes the changed value. A field changed
+/// differently on both sides takes the value of the winning side, or is
+/// recorded as a conflict if there is none.
+fn merge_field<T: Clone + PartialEq>(
+    field: &'static str,
+    (base, ours, theirs): (&T, &T, &T),
+    winner: Option<Side>,
+    format: impl Fn(&T) -> String,
+    conflicts: &mut Vec<FieldConflict>,
+) -> T {
+    if ours == theirs || theirs == base {
+        return ours.clone();
+    }
+    if ours == base {
+        return theirs.clone();
+    }
+
+    match winner {
+        Some(Side::Ours) => ours.clone(),
+        Some(Side::Theirs) => theirs.clone(),
+        None => {
+            conflicts.push(FieldConflict {
+                field,
+                ours: format(ours),
+                theirs: format(theirs),
+            });
+            ours.clone()
+        }
+    }
+}
+
+/// Format an optional field for display
+fn format_option(value: &Option<String>) -> String {
+    value.as_deref().unwrap_or("-").to_string()
+}
+
+/// Merge the local and remote versions of a task field by field
+///
+/// - Time spent on both sides since the base version is added up
+/// - Annotations added on both sides are kept
+/// - A finished or aborted status wins over a task still to do
+/// - Other fields changed on both sides take the value of the side updated
+///   last
+///
+/// Fields changed differently on both sides are resolved with the preferred
+/// side if any. Otherwise, fields that cannot be resolved by the rules above,
+/// such as a task finished on one side and aborted on the other, are
+/// reported as conflicts. Without a base version, the local version is used
+/// as the base.
+pub fn merge_tasks(
+    base: Option<&Task>,
+    ours: &Task,
+    theirs: &Task,
+    prefer: MergeStrategy,
+) -> TaskMerge {
+    let base = base.unwrap_or(ours);
+    let preferred = match prefer {
+        MergeStrategy::Local => Some(Side::Ours),
+        MergeStrategy::Remote => Some(Side::Theirs),
+        _ => None,
+    };
+    let latest = match ours.updated_at.cmp(&theirs.updated_at) {
+        std::cmp::Ordering::Greater => Some(Side::Ours),
+        std::cmp::Ordering::Less => Some(Side::Theirs),
+        std::cmp::Ordering::Equal => None,
+    };
+    let mut conflicts = Vec::new();
+
+    // The more final status wins
+    let status_winner = match (ours.status, theirs.status) {
+        (_, TaskStatus::Todo) => Some(Side::Ours),
+        (TaskStatus::Todo, _) => Some(Side::Theirs),
+        _ => preferred,
+    };
+    let status = merge_field(
+        "status",
+        (&base.status, &ours.status, &theirs.status),
+        status_winner,
+        TaskStatus::to_string,
+        &mut conflicts,
+    );
+    let completed_at = match status {
+        TaskStatus::Todo => None,
+        _ if ours.status == theirs.status => ours.completed_at.min(theirs.completed_at),
+        _ if status == ours.status => ours.completed_at,
+        _ => theirs.completed_at,
+    };
+
+    // Time spent on both sides is added up
+    let time_spent = match (base.time_spent, ours.time_spent, theirs.time_spent) {
+        (_, None, None) => None,
+        (base, ours, theirs) => Some(
+            (ours.unwrap_or_default() + theirs.unwrap_or_default())
+                .saturating_sub(base.unwrap_or_default()),
+        ),
+    };
+
+    // Annotations are only ever added
+    let mut annotations = ours.annotations.clone();
+    for annotation in &theirs.annotations {
+        if !annotations.contains(annotation) {
+            annotations.push(annotation.clone());
+        }
+    }
+    annotations.sort_by_key(|annotation| annotation.created_at);
+
+    // Other fields are resolved with the preferred side or the latest one
+    let winner = preferred.or(latest);
+    let task = Task {
+        id: ours.id.clone(),
+        description: merge_field(
+            "description",
+            (&base.description, &ours.description, &theirs.description),
+            winner,
+            String::clone,
+            &mut conflicts,
+        ),
+        priority: merge_field(
+            "priority",
+            (&base.priority, &ours.priority, &theirs.priority),
+            winner,
+            |priority| priority.to_string(),
+            &mut conflicts,
+        ),
+        scope: merge_field(
+            "scope",
+            (&base.scope, &ours.scope, &theirs.scope),
+            winner,
+            format_option,
+            &mut conflicts,
+        ),
+        task_type: merge_field(
+            "type",
+            (&base.task_type, &ours.task_type, &theirs.task_type),
+            winner,
+            format_option,
+            &mut conflicts,
+        ),
+        status,
+        created_at: ours.created_at.min(theirs.created_at),
+        updated_at: ours.updated_at.max(theirs.updated_at),
+        completed_at,
+        time_spent,
+        deleted_at: ours.deleted_at.max(theirs.deleted_at),
+        snoozed_until: merge_field(
+            "snoozed until",
+            (
+                &base.snoozed_until,
+                &ours.snoozed_until,
+                &theirs.snoozed_until,
+            ),
+            winner,
+            |until| until.map_or_else(|| "-".to_string(), |until| until.to_rfc3339()),
+            &mut conflicts,
+        ),
+        annotations,
+    };
+
+    TaskMerge { task, conflicts }
+}
+
+/// Merge the local and remote versions of a task file
+///
+/// Returns the merged content, or `None` if some fields conflict or the
+/// files cannot be parsed as tasks.
+pub fn merge_task_files(
+    base: Option<&str>,
+    ours: &str,
+    theirs: &str,
+    prefer: MergeStrategy,
+) -> Result<Option<String>> {
+    let parse = |content: &str| migration::parse_task(content).ok().map(|(task, _)| task);
+    let (Some(ours), Some(theirs)) = (parse(ours), parse(theirs)) else {
+        return Ok(None);
+    };
+    if ours.id != theirs.id {
+        return Ok(None);
+    }
+    let base = base.and_then(parse);
+
+    let merge = merge_tasks(base.as_ref(), &ours, &theirs, prefer);
+    if !merge.conflicts.is_empty() {
+        log::debug!(
+            "Conflicting fields in task {}: {:?}",
+            merge.task.id,
+            merge.conflicts
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(migration::serialize_task(&merge.task)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::*;
+    use crate::task::{Annotation, Priority};
+
+    fn create_base_task() -> Task {
+        let mut task = Task::new(
+            "task-1".to_string(),
+            "Write the report".to_string(),
+            Priority::Normal,
+            Some("work".to_string()),
+            None,
+        );
+        task.time_spent = Some(60);
+        task
+    }
+
+    /// Change a task at the given number of minutes after its creation
+    fn update(task: &Task, minutes: i64, change: impl FnOnce(&mut Task)) -> Task {
+        let mut task = task.clone();
+        change(&mut task);
+        task.updated_at = Some(task.created_at + Duration::minutes(minutes));
+        task
+    }
+
+    #[test]
+    fn test_merge_different_fields() {
+        let base = create_base_task();
+        let ours = update(&base, 1, |task| task.priority = Priority::High);
+        let theirs = update(&base, 2, |task| {
+            task.time_spent = Some(100);
+            task.task_type = Some("docs".to_string());
+        });
+
+        let merge = merge_tasks(Some(&base), &ours, &theirs, MergeStrategy::None);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.task.priority, Priority::High);
+        assert_eq!(merge.task.task_type, Some("docs".to_string()));
+        assert_eq!(merge.task.time_spent, Some(100));
+        assert_eq!(merge.task.updated_at, theirs.updated_at);
+    }
+
+    #[test]
+    fn test_merge_time_spent() {
+        let base = create_base_task();
+        let ours = update(&base, 1, |task| task.time_spent = Some(90));
+        let theirs = update(&base, 2, |task| task.time_spent = Some(100));
+
+        // Time added on both sides is kept
+        let merge = merge_tasks(Some(&base), &ours, &theirs, MergeStrategy::None);
+        assert_eq!(merge.task.time_spent, Some(130));
+    }
+
+    #[test]
+    fn test_merge_annotations() {
+        let base = create_base_task();
+        let annotate = |minutes, text: &str| {
+            update(&base, minutes, |task| {
+                task.annotations.push(Annotation {
+                    created_at: task.created_at + Duration::minutes(minutes),
+                    text: text.to_string(),
+                });
+            })
+        };
+        let ours = annotate(2, "Local note");
+        let theirs = annotate(1, "Remote note");
+
+        // Annotations added on both sides are kept in order
+        let merge = merge_tasks(Some(&base), &ours, &theirs, MergeStrategy::None);
+        assert!(merge.conflicts.is_empty());
+        let texts = merge
+            .task
+            .annotations
+            .iter()
+            .map(|annotation| annotation.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["Remote note", "Local note"]);
+    }
+
+    #[test]
+    fn test_merge_same_field() {
+        let base = create_base_task();
+        let ours = update(&base, 2, |task| task.priority = Priority::High);
+        let theirs = update(&base, 1, |task| task.priority = Priority::Low);
+
+        // The latest change wins
+        let merge = merge_tasks(Some(&base), &ours, &theirs, MergeStrategy::None);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.task.priority, Priority::High);
+
+        // Unless another side is preferred
+        let merge = merge_tasks(Some(&base), &ours, &theirs, MergeStrategy::Remote);
+        assert_eq!(merge.task.priority, Priority::Low);
+
+        // Changes made at the same time conflict
+        let theirs = update(&base, 2, |task| task.priority = Priority::Low);
+        let merge = merge_tasks(Some(&base), &ours, &theirs, MergeStrategy::None);
+        assert_eq!(
+            merge.conflicts,
+            vec![FieldConflict {
+                field: "priority",
+                ours: "high".to_string(),
+                theirs: "low".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_status() {
+        let base = create_base_task();
+        let now = Local::now().fixed_offset();
+        let done = update(&base, 1, |task| {
+            task.status = TaskStatus::Done;
+            task.completed_at = Some(now);
+        });
+        let edited = update(&base, 2, |task| task.description = "New".to_string());
+
+        // A finished task stays finished
+        let merge = merge_tasks(Some(&base), &edited, &done, MergeStrategy::None);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.task.status, TaskStatus::Done);
+        assert_eq!(merge.task.completed_at, Some(now));
+        assert_eq!(merge.task.description, "New");
+
+        // A task finished on one side and aborted on the other conflicts
+        let aborted = update(&base, 2, |task| {
+            task.status = TaskStatus::Aborted;
+            task.completed_at = Some(now);
+        });
+        let merge = merge_tasks(Some(&base), &done, &aborted, MergeStrategy::None);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].field, "status");
+        let merge = merge_tasks(Some(&base), &done, &aborted, MergeStrategy::Remote);
+        assert_eq!(merge.task.status, TaskStatus::Aborted);
+    }
+
+    #[test]
+    fn test_merge_task_files() {
+        let base = create_base_task();
+        let ours = update(&base, 1, |task| task.priority = Priority::High);
+        let theirs = update(&base, 2, |task| task.time_spent = Some(100));
+        let serialize = |task: &Task| migration::serialize_task(task).unwrap();
+
+        let merged = merge_task_files(
+            Some(&serialize(&base)),
+            &serialize(&ours),
+            &serialize(&theirs),
+            MergeStrategy::None,
+        )
+        .unwrap()
+        .unwrap();
+        let (merged, _) = migration::parse_task(&merged).unwrap();
+        assert_eq!(merged.priority, Priority::High);
+        assert_eq!(merged.time_spent, Some(100));
+
+        // Files that are not tasks are left to the user
+        assert!(
+            merge_task_files(None, "not a task", &serialize(&ours), MergeStrategy::None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_resolve_conflict() {
+        let base = create_base_task();
+        let ours = update(&base, 1, |task| {
+            task.priority = Priority::High;
+            task.scope = Some("home".to_string());
+        });
+        let theirs = update(&base, 1, |task| {
+            task.priority = Priority::Low;
+            task.scope = Some("school".to_string());
+        });
+        let serialize = |task: &Task| migration::serialize_task(task).unwrap();
+        let conflict = TaskConflict::new(&ConflictedFile {
+            path: "task-1.toml".into(),
+            base: Some(serialize(&base)),
+            ours: Some(serialize(&ours)),
+            theirs: Some(serialize(&theirs)),
+        });
+        assert_eq!(conflict.description(), Some("Write the report"));
+        let fields = conflict
+            .fields
+            .iter()
+            .map(|field| field.field)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec!["priority", "scope"]);
+
+        // Pick a whole side
+        let resolved = conflict.resolve(&Resolution::Side(Side::Theirs)).unwrap();
+        assert_eq!(resolved, Some(serialize(&theirs)));
+
+        // Pick a side for each field
+        let resolved = conflict
+            .resolve(&Resolution::Fields(vec![Side::Ours, Side::Theirs]))
+            .unwrap()
+            .unwrap();
+        let (task, _) = migration::parse_task(&resolved).unwrap();
+        assert_eq!(task.priority, Priority::High);
+        assert_eq!(task.scope, Some("school".to_string()));
+
+        // Edited content must be a task
+        assert!(
+            conflict
+                .resolve(&Resolution::Content("not a task".to_string()))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::Context;
+use serde::Serialize;
+use toml::{Table, Value};
+
+use super::Task;
+use crate::{Error, Result};
+
+/// Current schema version of task files
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Key holding the schema version in task files
+const VERSION_KEY: &str = "version";
+
+/// Migration upgrading a task file by one schema version
+type Migration = fn(&mut Table) -> Result<()>;
+
+/// Migrations to apply in order, indexed by the version they upgrade from
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Task wrapper used to write the schema version along with the task
+#[derive(Serialize)]
+struct VersionedTask<'a> {
+    version: u32,
+    #[serde(flatten)]
+    task: &'a Task,
+}
+
+/// Version 0 files have no version marker but the same fields as version 1
+const fn v0_to_v1(_task: &mut Table) -> Result<()> {
+    Ok(())
+}
+
+/// Version 2 adds the optional `snoozed_until` and `annotations` fields,
+/// missing from version 1 files
+const fn v1_to_v2(_task: &mut Table) -> Result<()> {
+    Ok(())
+}
+
+/// Get the schema version of a task file
+fn schema_version(table: &Table) -> Result<u32> {
+    match table.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(Value::Integer(version)) => {
+            Ok(u32::try_from(*version).context("Invalid schema version in task file")?)
+        }
+        Some(_) => Err(anyhow::anyhow!("Invalid schema version in task file").into()),
+    }
+}
+
+/// Parse a task file, upgrading it to the current schema version
+///
+/// Returns the task and whether it was written with an older schema version.
+pub fn parse_task(contents: &str) -> Result<(Task, bool)> {
+    let mut table: Table = toml::from_str(contents)?;
+    let version = schema_version(&table)?;
+    if version > SCHEMA_VERSION {
+        return Err(Error::UnsupportedSchema {
+            version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut table)?;
+    }
+    table.remove(VERSION_KEY);
+
+    let task = Value::Table(table).try_into()?;
+    Ok((task, version < SCHEMA_VERSION))
+}
+
+/// Serialize a task with the current schema version
+pub fn serialize_task(task: &Task) -> Result<String> {
+    Ok(toml::to_string(&VersionedTask {
+        version: SCHEMA_VERSION,
+        task,
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Priority;
+
+    #[test]
+    fn test_round_trip() {
+        let task = Task::new(
+            "test-id".to_string(),
+            "Test task".to_string(),
+            Priority::High,
+            None,
+            Some("feat".to_string()),
+        );
+
+        let contents = serialize_task(&task).unwrap();
+        assert!(contents.starts_with(&format!("version = {SCHEMA_VERSION}")));
+
+        let (parsed, migrated) = parse_task(&contents).unwrap();
+        assert!(!migrated);
+        assert_eq!(parsed.id, task.id);
+        assert_eq!(parsed.priority, task.priority);
+        assert_eq!(parsed.task_type, task.task_type);
+        assert_eq!(parsed.scope, None);
+    }
+
+    #[test]
+    fn test_migrate_unversioned_file() {
+        let task = Task::new(
+            "test-id".to_string(),
+            "Test task".to_string(),
+            Priority::Normal,
+            None,
+            None,
+        );
+        let contents = toml::to_string(&task).unwrap();
+
+        let (parsed, migrated) = parse_task(&contents).unwrap();
+        assert!(migrated);
+        assert_eq!(parsed.id, task.id);
+    }
+
+    #[test]
+    fn test_migrate_v1_file() {
+        let task = Task::new(
+            "test-id".to_string(),
+            "Test task".to_string(),
+            Priority::Normal,
+            None,
+            None,
+        );
+        let contents = serialize_task(&task).unwrap().replacen(
+            &format!("version = {SCHEMA_VERSION}"),
+            "version = 1",
+            1,
+        );
+
+        let (parsed, migrated) = parse_task(&contents).unwrap();
+        assert!(migrated);
+        assert_eq!(parsed.snoozed_until, None);
+        assert!(parsed.annotations.is_empty());
+    }
+
+    #[test]
+    fn test_reject_newer_file() {
+        let task = Task::new(
+            "test-id".to_string(),
+            "Test task".to_string(),
+            Priority::Normal,
+            None,
+            None,
+        );
+        let contents = serialize_task(&task).unwrap().replacen(
+            &format!("version = {SCHEMA_VERSION}"),
+            &format!("version = {}", SCHEMA_VERSION + 1),
+            1,
+        );
+
+        let error = parse_task(&contents).unwrap_err();
+        assert!(matches!(error, Error::UnsupportedSchema { .. }));
+        assert!(error.to_string().contains("upgrade rutd"));
+    }
+}
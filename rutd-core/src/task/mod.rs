@@ -1,11 +1,22 @@
 pub mod active_task;
 pub mod filter;
+pub mod history;
 pub mod manager;
+pub mod merge;
+pub mod migration;
 pub mod model;
+pub mod pending_sync;
 pub mod sort;
 pub mod storage;
+pub mod working_set;
 
 pub use filter::{DateRange, Filter};
+pub use history::{
+    ActivityEntry, ActivityGroup, ActivityTask, ChangeKind, TaskChange, TaskEvent, TaskLogEntry,
+    UndoPlan,
+};
 pub use manager::TaskManager;
-pub use model::{Priority, Task, TaskStatus};
+pub use merge::{FieldConflict, Resolution, Side, TaskConflict};
+pub use model::{Annotation, Modification, Priority, Task, TaskStatus};
 pub use sort::{SortCriteria, SortOptions, SortOrder, sort_tasks};
+pub use working_set::{SHORT_ID_LENGTH, WorkingSet};
@@ -1,10 +1,11 @@
 use std::fmt;
 
-use anyhow::Result;
-use chrono::Local;
+use chrono::{DateTime, FixedOffset, Local};
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumMessage, EnumString};
 
+use crate::{Error, Result};
+
 // FIXME: Visible aliases for value enum is not yet supported in clap, see
 // https://github.com/clap-rs/clap/pull/5480
 /// Task Priority
@@ -89,8 +90,10 @@ impl TaskStatus {
     pub fn done(self) -> Result<Self> {
         match self {
             TaskStatus::Todo => Ok(TaskStatus::Done),
-            TaskStatus::Done => anyhow::bail!("Task is already done."),
-            TaskStatus::Aborted => anyhow::bail!("Task is aborted and cannot be marked done."),
+            from => Err(Error::InvalidTransition {
+                from,
+                to: TaskStatus::Done,
+            }),
         }
     }
 
@@ -98,8 +101,10 @@ impl TaskStatus {
     pub fn aborted(self) -> Result<Self> {
         match self {
             TaskStatus::Todo => Ok(TaskStatus::Aborted),
-            TaskStatus::Done => anyhow::bail!("Task is done and cannot be aborted."),
-            TaskStatus::Aborted => anyhow::bail!("Task is already aborted."),
+            from => Err(Error::InvalidTransition {
+                from,
+                to: TaskStatus::Aborted,
+            }),
         }
     }
 
@@ -107,8 +112,57 @@ impl TaskStatus {
     pub fn start(self) -> Result<Self> {
         match self {
             TaskStatus::Todo => Ok(TaskStatus::Todo),
-            TaskStatus::Done => anyhow::bail!("Task is done and cannot be started."),
-            TaskStatus::Aborted => anyhow::bail!("Task is aborted and cannot be started."),
+            from => Err(Error::InvalidTransition {
+                from,
+                to: TaskStatus::Todo,
+            }),
+        }
+    }
+}
+
+/// Serde helpers keeping timestamps in the RFC 3339 format written by
+/// earlier versions
+mod rfc3339 {
+    use chrono::{DateTime, FixedOffset};
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(
+        time: &DateTime<FixedOffset>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&time.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<FixedOffset>, D::Error> {
+        let time = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&time)
+            .map_err(|e| D::Error::custom(format!("invalid timestamp {time:?}: {e}")))
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            time: &Option<DateTime<FixedOffset>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<FixedOffset>>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|time| {
+                    DateTime::parse_from_rfc3339(&time)
+                        .map_err(|e| D::Error::custom(format!("invalid timestamp {time:?}: {e}")))
+                })
+                .transpose()
         }
     }
 }
@@ -128,14 +182,54 @@ pub struct Task {
     pub task_type: Option<String>,
     /// Task status
     pub status: TaskStatus,
-    /// Task creation time in ISO format
-    pub created_at: String,
-    /// Task last update time in ISO format
-    pub updated_at: Option<String>,
-    /// Task completion time in ISO format
-    pub completed_at: Option<String>,
+    /// Task creation time, stored in ISO format
+    #[serde(with = "rfc3339")]
+    pub created_at: DateTime<FixedOffset>,
+    /// Task last update time, stored in ISO format
+    #[serde(
+        default,
+        with = "rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime<FixedOffset>>,
+    /// Task completion time, stored in ISO format
+    #[serde(
+        default,
+        with = "rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub completed_at: Option<DateTime<FixedOffset>>,
     /// Time spent on task in seconds
     pub time_spent: Option<u64>,
+    /// Task deletion time, stored in ISO format, only set for tasks in the
+    /// trash
+    #[serde(
+        default,
+        with = "rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deleted_at: Option<DateTime<FixedOffset>>,
+    /// Time until which the task is hidden from the task list, stored in ISO
+    /// format
+    #[serde(
+        default,
+        with = "rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub snoozed_until: Option<DateTime<FixedOffset>>,
+    /// Notes added to the task, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+/// Note added to a task
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Annotation {
+    /// Time the note was added, stored in ISO format
+    #[serde(with = "rfc3339")]
+    pub created_at: DateTime<FixedOffset>,
+    /// Text of the note
+    pub text: String,
 }
 
 impl Task {
@@ -153,10 +247,50 @@ impl Task {
             scope,
             task_type,
             status: TaskStatus::Todo,
-            created_at: Local::now().to_rfc3339(),
+            created_at: Local::now().fixed_offset(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Check if the task is hidden until a later time
+    pub fn is_snoozed(&self) -> bool {
+        self.snoozed_until
+            .is_some_and(|until| until > Local::now().fixed_offset())
+    }
+}
+
+/// Changes to apply to tasks, fields left as `None` are kept unchanged
+#[derive(Clone, Debug, Default)]
+pub struct Modification {
+    /// New task priority
+    pub priority: Option<Priority>,
+    /// New task scope
+    pub scope: Option<String>,
+    /// New task type
+    pub task_type: Option<String>,
+}
+
+impl Modification {
+    /// Check if no change is requested
+    pub const fn is_empty(&self) -> bool {
+        self.priority.is_none() && self.scope.is_none() && self.task_type.is_none()
+    }
+
+    /// Apply the changes to a task
+    pub fn apply(&self, task: &mut Task) {
+        if let Some(priority) = self.priority {
+            task.priority = priority;
+        }
+        if let Some(scope) = &self.scope {
+            task.scope = Some(scope.clone());
+        }
+        if let Some(task_type) = &self.task_type {
+            task.task_type = Some(task_type.clone());
         }
     }
 }
@@ -193,8 +327,8 @@ mod tests {
         assert!(task.completed_at.is_none());
         assert!(task.time_spent.is_none());
 
-        // Verify creation time is in RFC3339 format
-        assert!(chrono::DateTime::parse_from_rfc3339(&task.created_at).is_ok());
+        // Verify creation time is set to now
+        assert!(task.created_at <= Local::now());
     }
 
     #[test]
@@ -252,10 +386,13 @@ mod tests {
             scope: Some("test-scope".to_string()),
             task_type: Some("test-type".to_string()),
             status: TaskStatus::Todo,
-            created_at: "2023-01-01T12:00:00+00:00".to_string(),
-            updated_at: Some("2023-01-02T12:00:00+00:00".to_string()),
+            created_at: DateTime::parse_from_rfc3339("2023-01-01T12:00:00+00:00").unwrap(),
+            updated_at: Some(DateTime::parse_from_rfc3339("2023-01-02T12:00:00+00:00").unwrap()),
             completed_at: None,
             time_spent: Some(3600),
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         // Serialize to TOML
@@ -289,6 +426,33 @@ mod tests {
         assert_eq!(deserialized_task.time_spent, original_task.time_spent);
     }
 
+    #[test]
+    fn test_task_timestamp_parsing() {
+        let toml_string = r#"
+            id = "test-id"
+            description = "Test description"
+            priority = "Normal"
+            status = "Done"
+            created_at = "2023-01-01T12:00:00.123456789+08:00"
+            completed_at = "2023-01-02T12:00:00Z"
+        "#;
+
+        // Timestamps written by older versions are still accepted
+        let task: Task = toml::from_str(toml_string).unwrap();
+        assert_eq!(
+            task.created_at,
+            DateTime::parse_from_rfc3339("2023-01-01T12:00:00.123456789+08:00").unwrap()
+        );
+        assert_eq!(
+            task.completed_at,
+            Some(DateTime::parse_from_rfc3339("2023-01-02T12:00:00+00:00").unwrap())
+        );
+
+        // Invalid timestamps are rejected when loading
+        let invalid = toml_string.replace("2023-01-02T12:00:00Z", "yesterday");
+        assert!(toml::from_str::<Task>(&invalid).is_err());
+    }
+
     #[test]
     fn test_priority_clone_and_copy() {
         let p1 = Priority::Urgent;
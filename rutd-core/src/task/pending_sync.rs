@@ -0,0 +1,104 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Automatic sync that failed and is retried by the next command
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PendingSync {
+    /// Time when the sync failed
+    pub failed_at: String,
+    /// Why the sync failed
+    pub error: String,
+}
+
+impl PendingSync {
+    /// Create a new pending sync record
+    pub const fn new(failed_at: String, error: String) -> Self {
+        Self { failed_at, error }
+    }
+}
+
+/// Record a failed automatic sync
+pub fn save_pending_sync(file_path: &Path, pending: &PendingSync) -> Result<()> {
+    log::debug!("Saving pending sync to {}", file_path.display());
+
+    // Make sure the directory exists
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let toml_string = toml::to_string(pending)?;
+    let mut file = File::create(file_path)?;
+    file.write_all(toml_string.as_bytes())?;
+
+    Ok(())
+}
+
+/// Load the failed automatic sync, if any
+pub fn load_pending_sync(file_path: &Path) -> Result<Option<PendingSync>> {
+    log::trace!("Checking for pending sync at {}", file_path.display());
+
+    if !file_path.exists() {
+        return Ok(None);
+    }
+
+    let mut contents = String::new();
+    let mut file = File::open(file_path).context(format!(
+        "Failed to open pending sync file at {}",
+        file_path.display()
+    ))?;
+    file.read_to_string(&mut contents)?;
+
+    let pending: PendingSync =
+        toml::from_str(&contents).context("Failed to parse pending sync TOML")?;
+
+    Ok(Some(pending))
+}
+
+/// Forget the failed automatic sync once tasks are synced
+pub fn clear_pending_sync(file_path: &Path) -> Result<()> {
+    if !file_path.exists() {
+        return Ok(());
+    }
+
+    fs::remove_file(file_path)?;
+
+    log::debug!("Cleared pending sync");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_pending_sync_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("pending_sync.toml");
+        assert!(load_pending_sync(&file_path).unwrap().is_none());
+
+        let pending = PendingSync::new(
+            "2026-01-01T00:00:00+00:00".to_string(),
+            "failed to connect".to_string(),
+        );
+        save_pending_sync(&file_path, &pending).unwrap();
+
+        let loaded = load_pending_sync(&file_path).unwrap().unwrap();
+        assert_eq!(loaded.failed_at, pending.failed_at);
+        assert_eq!(loaded.error, pending.error);
+
+        clear_pending_sync(&file_path).unwrap();
+        assert!(load_pending_sync(&file_path).unwrap().is_none());
+        // Clearing twice is fine
+        clear_pending_sync(&file_path).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset};
 use strum::{EnumIter, EnumMessage, EnumString};
 
 use super::Task;
@@ -135,18 +135,16 @@ fn compare_option_string(a: &Option<String>, b: &Option<String>) -> std::cmp::Or
     }
 }
 
-// Helper function to compare times in RFC3339 format
-fn compare_times(a: &str, b: &str) -> std::cmp::Ordering {
-    let a_time = parse_time(a);
-    let b_time = parse_time(b);
-    match (a_time, b_time) {
-        (Ok(a_dt), Ok(b_dt)) => a_dt.cmp(&b_dt),
-        _ => std::cmp::Ordering::Equal, // Fall back to equality for parse errors
-    }
+// Helper function to compare times
+fn compare_times(a: &DateTime<FixedOffset>, b: &DateTime<FixedOffset>) -> std::cmp::Ordering {
+    a.cmp(b)
 }
 
 // Helper function to compare optional times
-fn compare_option_times(a: &Option<String>, b: &Option<String>) -> std::cmp::Ordering {
+fn compare_option_times(
+    a: &Option<DateTime<FixedOffset>>,
+    b: &Option<DateTime<FixedOffset>>,
+) -> std::cmp::Ordering {
     match (a, b) {
         (Some(a_val), Some(b_val)) => compare_times(a_val, b_val),
         (Some(_), None) => std::cmp::Ordering::Less,
@@ -165,11 +163,6 @@ fn compare_option_numbers<T: Ord>(a: &Option<T>, b: &Option<T>) -> std::cmp::Ord
     }
 }
 
-// Helper function to parse time from RFC3339 format
-fn parse_time(time_str: &str) -> Result<DateTime<Local>, chrono::ParseError> {
-    DateTime::parse_from_rfc3339(time_str).map(|dt| dt.with_timezone(&Local))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,10 +200,13 @@ mod tests {
             scope: scope.map(|s| s.to_string()),
             task_type: task_type.map(|t| t.to_string()),
             status,
-            created_at: created_at.to_string(),
-            updated_at: updated_at.map(|s| s.to_string()),
-            completed_at: completed_at.map(|s| s.to_string()),
+            created_at: DateTime::parse_from_rfc3339(created_at).unwrap(),
+            updated_at: updated_at.map(|s| DateTime::parse_from_rfc3339(s).unwrap()),
+            completed_at: completed_at.map(|s| DateTime::parse_from_rfc3339(s).unwrap()),
             time_spent,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         }
     }
 
@@ -1,13 +1,28 @@
 use std::{
     fs::{self, File},
-    io::{Read, Write},
+    io::Read,
     path::{Path, PathBuf},
 };
 
-use anyhow::Result;
+use chrono::Local;
 
-use super::Task;
-use crate::git::repo::GitRepo;
+use super::{
+    Task,
+    migration::{self, SCHEMA_VERSION},
+};
+use crate::{
+    Error, Result,
+    git::{changes::ChangeSet, history::MIGRATE_ACTION, repo::GitRepo},
+};
+
+/// Name of the directory (relative to the tasks directory) holding deleted
+/// tasks
+pub const TRASH_DIR: &str = ".trash";
+
+/// Get the trash directory for a tasks directory
+pub fn trash_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join(TRASH_DIR)
+}
 
 /// Save task to TOML file
 pub fn save_task(
@@ -16,35 +31,82 @@ pub fn save_task(
     after_action: &str,
     description: &str,
 ) -> Result<()> {
-    // Make sure the tasks directory exists
-    fs::create_dir_all(root_dir)?;
+    save_tasks(
+        root_dir,
+        std::slice::from_ref(task),
+        after_action,
+        description,
+    )
+}
 
-    // Initialize the Git repository
-    let git_repo = GitRepo::init(root_dir)?;
+/// Save tasks to TOML files, recording all of them in a single commit
+pub fn save_tasks(
+    root_dir: &Path,
+    tasks: &[Task],
+    after_action: &str,
+    description: &str,
+) -> Result<()> {
+    let changes = plan_save_tasks(root_dir, tasks, after_action, description)?;
+    apply_changes(root_dir, &changes)
+}
 
-    // Use the task's UUID as the filename
-    let file_path = root_dir.join(format!("{}.toml", task.id));
+/// Prepare the changes made by [`save_tasks`] without writing anything
+pub fn plan_save_tasks(
+    root_dir: &Path,
+    tasks: &[Task],
+    after_action: &str,
+    description: &str,
+) -> Result<ChangeSet> {
+    let mut changes = ChangeSet::default();
+    for task in tasks {
+        // Use the task's UUID as the filename
+        changes.write(
+            root_dir,
+            Path::new(&format!("{}.toml", task.id)),
+            migration::serialize_task(task)?,
+        )?;
+    }
 
-    // Serialize the task to TOML format
-    let toml_string = toml::to_string(task)?;
+    changes.message = commit_message(tasks, after_action, description);
 
-    // Write the serialized TOML string to a file
-    let mut file = File::create(file_path)?;
-    file.write_all(toml_string.as_bytes())?;
+    Ok(changes)
+}
 
-    // Automatically commit changes
-    let scope = task.scope.as_deref();
-    let task_type = task.task_type.as_deref();
-    let commit_message = GitRepo::generate_commit_message(
-        after_action,
-        scope,
-        task_type,
+/// Generate the message of a commit changing the given tasks
+///
+/// The scope and type are only part of the commit message if they are shared
+/// by all tasks.
+pub fn commit_message(tasks: &[Task], action: &str, description: &str) -> String {
+    let shared = |field: fn(&Task) -> Option<&str>| {
+        let first = tasks.first().and_then(field);
+        tasks
+            .iter()
+            .all(|task| field(task) == first)
+            .then_some(first)
+            .flatten()
+    };
+    let ids = tasks
+        .iter()
+        .map(|task| task.id.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    GitRepo::generate_commit_message(
+        action,
+        shared(|task| task.scope.as_deref()),
+        shared(|task| task.task_type.as_deref()),
         description,
-        task.id.as_str(),
-    );
-    git_repo.commit_changes(&commit_message)?;
+        &ids,
+    )
+}
+
+/// Write the changed task files and commit them
+pub fn apply_changes(root_dir: &Path, changes: &ChangeSet) -> Result<()> {
+    // Make sure the tasks directory exists
+    fs::create_dir_all(root_dir)?;
 
-    Ok(())
+    // Initialize the Git repository
+    let git_repo = GitRepo::init(root_dir)?;
+    git_repo.apply(changes)
 }
 
 /// Locate all potential task files by ID
@@ -76,6 +138,17 @@ pub fn locate_all_tasks(root_dir: &Path, task_id: &str) -> Result<Vec<PathBuf>>
     Ok(matching_files)
 }
 
+/// Get the task IDs from the names of task files
+fn task_ids(files: &[PathBuf]) -> Vec<String> {
+    let mut ids = files
+        .iter()
+        .filter_map(|file| file.file_stem().and_then(|s| s.to_str()))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
 /// Locate task file by ID
 ///
 /// This function utilizes the `locate_all_tasks` function to find all potential
@@ -85,15 +158,18 @@ pub fn locate_task(root_dir: &Path, task_id: &str) -> Result<PathBuf> {
     // Make sure the directory exists
     // Make sure the directory exists
     if !root_dir.exists() {
-        anyhow::bail!("Tasks directory does not exist");
+        return Err(anyhow::anyhow!("Tasks directory does not exist").into());
     }
 
     let matching_files = locate_all_tasks(root_dir, task_id)?;
 
     match matching_files.len() {
         1 => Ok(matching_files[0].to_owned()),
-        0 => anyhow::bail!("No task found with ID starting with {}", task_id),
-        _ => anyhow::bail!("Multiple tasks found with ID starting with {}", task_id),
+        0 => Err(Error::NotFound(task_id.to_string())),
+        _ => Err(Error::AmbiguousId {
+            id: task_id.to_string(),
+            candidates: task_ids(&matching_files),
+        }),
     }
 }
 
@@ -106,19 +182,47 @@ pub fn load_task(root_dir: &Path, task_id: &str) -> Result<Task> {
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
 
-    // Deserialize the TOML content into a Task struct
-    let task = toml::from_str(&contents)?;
+    // Deserialize the TOML content into a Task struct, upgrading it if needed
+    let (task, _) = migration::parse_task(&contents)?;
 
     Ok(task)
 }
 
+/// Task file that could not be loaded
+#[derive(Debug)]
+pub struct InvalidTaskFile {
+    /// Path of the task file
+    pub path: PathBuf,
+    /// Why the task file could not be loaded
+    pub error: Error,
+}
+
 /// Load all tasks
+///
+/// Invalid task files are skipped with a warning in the log, see
+/// [`load_all_tasks_checked`] to get them.
 pub fn load_all_tasks(root_dir: &Path) -> Result<Vec<Task>> {
+    let (tasks, invalid_files) = load_all_tasks_checked(root_dir)?;
+    for file in invalid_files {
+        log::warn!(
+            "Skipping invalid task file {}: {}",
+            file.path.display(),
+            file.error
+        );
+    }
+    Ok(tasks)
+}
+
+/// Load all tasks, along with the task files that could not be loaded
+///
+/// Fails if a task file was written by a newer rutd.
+pub fn load_all_tasks_checked(root_dir: &Path) -> Result<(Vec<Task>, Vec<InvalidTaskFile>)> {
     let mut tasks = Vec::new();
+    let mut invalid_files = Vec::new();
 
     // Make sure the directory exists
     if !root_dir.exists() {
-        return Ok(tasks);
+        return Ok((tasks, invalid_files));
     }
 
     // Iterate over all TOML files in the directory
@@ -131,17 +235,39 @@ pub fn load_all_tasks(root_dir: &Path) -> Result<Vec<Task>> {
             let mut file = File::open(&path)?;
             file.read_to_string(&mut contents)?;
 
-            if let Ok(task) = toml::from_str(&contents) {
-                tasks.push(task);
+            // Skip invalid files, unless they were written by a newer rutd
+            match migration::parse_task(&contents) {
+                Ok((task, _)) => tasks.push(task),
+                Err(e @ Error::UnsupportedSchema { .. }) => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("Failed to load {}", path.display()))
+                        .into());
+                }
+                Err(error) => invalid_files.push(InvalidTaskFile { path, error }),
             }
         }
     }
 
-    Ok(tasks)
+    // Report invalid files in a stable order
+    invalid_files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((tasks, invalid_files))
 }
 
-/// Delete task file
+/// Move task files to the trash
+///
+/// The tasks are marked with their deletion time and moved into the trash
+/// directory, so that they can be restored later with `restore_task`.
 pub fn delete_task(root_dir: &Path, task_ids: &[&str]) -> Result<()> {
+    let changes = plan_delete_task(root_dir, task_ids)?;
+    apply_changes(root_dir, &changes)
+}
+
+/// Prepare the changes made by [`delete_task`] without writing anything
+pub fn plan_delete_task(root_dir: &Path, task_ids: &[&str]) -> Result<ChangeSet> {
+    let trash_dir = trash_dir(root_dir);
+
+    let deleted_at = Local::now().fixed_offset();
+    let mut changes = ChangeSet::default();
     let mut ids = Vec::new();
     for task_id in task_ids {
         // First load the task to get its scope and type before deleting
@@ -150,27 +276,177 @@ pub fn delete_task(root_dir: &Path, task_ids: &[&str]) -> Result<()> {
         // Read task data to get scope and type
         let mut file_content = String::new();
         File::open(&file)?.read_to_string(&mut file_content)?;
-        let task: Task = toml::from_str(&file_content)?;
+        let (mut task, _) = migration::parse_task(&file_content)?;
 
         // Save the id for commit message
         ids.push(task.id.clone());
 
-        // Now delete the file
-        fs::remove_file(file)?;
+        // Mark the task as deleted and move it to the trash
+        task.deleted_at = Some(deleted_at);
+        changes.write(
+            root_dir,
+            &trash_dir.join(format!("{}.toml", task.id)),
+            migration::serialize_task(&task)?,
+        )?;
+        changes.remove(root_dir, &file)?;
     }
 
-    // Initialize the Git repository
-    let git_repo = GitRepo::init(root_dir)?;
-
     // Automatically commit changes with improved commit message
-    let commit_message =
+    changes.message =
         GitRepo::generate_commit_message("delete", None, None, "Delete tasks", &ids.join("\n"));
-    git_repo.commit_changes(&commit_message)?;
-    Ok(())
+    Ok(changes)
+}
+
+/// Load all tasks in the trash
+pub fn load_trashed_tasks(root_dir: &Path) -> Result<Vec<Task>> {
+    load_all_tasks(&trash_dir(root_dir))
+}
+
+/// Restore a deleted task
+///
+/// The task is looked up in the trash first. Tasks deleted before the trash
+/// existed (or purged from it) are recovered from the git history instead.
+pub fn restore_task(root_dir: &Path, task_id: &str) -> Result<Task> {
+    let (task, changes) = plan_restore_task(root_dir, task_id)?;
+    apply_changes(root_dir, &changes)?;
+    Ok(task)
+}
+
+/// Prepare the changes made by [`restore_task`] without writing anything
+pub fn plan_restore_task(root_dir: &Path, task_id: &str) -> Result<(Task, ChangeSet)> {
+    let trash_dir = trash_dir(root_dir);
+
+    let (mut task, trashed_file) = match locate_all_tasks(&trash_dir, task_id) {
+        Ok(files) if files.len() > 1 => {
+            return Err(Error::AmbiguousId {
+                id: task_id.to_string(),
+                candidates: task_ids(&files),
+            });
+        }
+        Ok(files) if files.len() == 1 => {
+            let contents = fs::read_to_string(&files[0])?;
+            (migration::parse_task(&contents)?.0, Some(files[0].clone()))
+        }
+        // Fall back to the git history if the task is not in the trash, without
+        // creating a repository when there is none
+        _ => {
+            let contents = match GitRepo::open(root_dir) {
+                Ok(git_repo) => git_repo.find_deleted_task(task_id)?,
+                Err(_) => None,
+            };
+            let Some(contents) = contents else {
+                return Err(Error::NotFound(task_id.to_string()));
+            };
+            (migration::parse_task(&contents)?.0, None)
+        }
+    };
+
+    let file_path = root_dir.join(format!("{}.toml", task.id));
+    if file_path.exists() {
+        return Err(anyhow::anyhow!("Task {} already exists", task.id).into());
+    }
+
+    // Clear the deletion mark and move the task back
+    task.deleted_at = None;
+    let mut changes = ChangeSet::default();
+    changes.write(root_dir, &file_path, migration::serialize_task(&task)?)?;
+    if let Some(trashed_file) = trashed_file {
+        changes.remove(root_dir, &trashed_file)?;
+    }
+
+    changes.message = GitRepo::generate_commit_message(
+        "restore",
+        task.scope.as_deref(),
+        task.task_type.as_deref(),
+        "Restore task",
+        &task.id,
+    );
+
+    Ok((task, changes))
+}
+
+/// Permanently remove tasks from the trash
+pub fn purge_task(root_dir: &Path, task_ids: &[&str]) -> Result<()> {
+    let changes = plan_purge_task(root_dir, task_ids)?;
+    apply_changes(root_dir, &changes)
+}
+
+/// Prepare the changes made by [`purge_task`] without writing anything
+pub fn plan_purge_task(root_dir: &Path, task_ids: &[&str]) -> Result<ChangeSet> {
+    let trash_dir = trash_dir(root_dir);
+
+    let mut changes = ChangeSet::default();
+    let mut ids = Vec::new();
+    for task_id in task_ids {
+        let file = locate_task(&trash_dir, task_id)?;
+        ids.push(task_id.to_string());
+        changes.remove(root_dir, &file)?;
+    }
+
+    changes.message =
+        GitRepo::generate_commit_message("purge", None, None, "Empty trash", &ids.join("\n"));
+    Ok(changes)
+}
+
+/// Upgrade task files written with an older schema version
+///
+/// Both the tasks and the trash are upgraded, and the rewritten files are
+/// committed at once. Returns the IDs of the upgraded tasks.
+pub fn migrate_tasks(root_dir: &Path) -> Result<Vec<String>> {
+    let (ids, changes) = plan_migrate_tasks(root_dir)?;
+    if !changes.is_empty() {
+        apply_changes(root_dir, &changes)?;
+    }
+    Ok(ids)
+}
+
+/// Prepare the changes made by [`migrate_tasks`] without writing anything
+pub fn plan_migrate_tasks(root_dir: &Path) -> Result<(Vec<String>, ChangeSet)> {
+    let mut changes = ChangeSet::default();
+    let mut ids = Vec::new();
+    for dir in [root_dir.to_path_buf(), trash_dir(root_dir)] {
+        if !dir.exists() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("toml") {
+                continue;
+            }
+
+            let contents = fs::read_to_string(&path)?;
+            let task = match migration::parse_task(&contents) {
+                Ok((task, true)) => task,
+                Ok((_, false)) => continue,
+                Err(e @ Error::UnsupportedSchema { .. }) => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("Failed to migrate {}", path.display()))
+                        .into());
+                }
+                // Leave files that are not valid tasks untouched
+                Err(_) => continue,
+            };
+            changes.write(root_dir, &path, migration::serialize_task(&task)?)?;
+            ids.push(task.id);
+        }
+    }
+
+    changes.message = GitRepo::generate_commit_message(
+        MIGRATE_ACTION,
+        None,
+        None,
+        &format!("Migrate tasks to schema version {SCHEMA_VERSION}"),
+        &ids.join("\n"),
+    );
+
+    Ok((ids, changes))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Write;
+
+    use chrono::DateTime;
     use tempfile::tempdir;
 
     use super::*;
@@ -185,10 +461,13 @@ mod tests {
             scope: Some("test-scope".to_string()),
             task_type: Some("test-type".to_string()),
             status: TaskStatus::Todo,
-            created_at: "2023-01-01T12:00:00+00:00".to_string(),
+            created_at: DateTime::parse_from_rfc3339("2023-01-01T12:00:00+00:00").unwrap(),
             updated_at: None,
             completed_at: None,
             time_spent: None,
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         }
     }
 
@@ -275,10 +554,65 @@ mod tests {
                 .contains("Multiple tasks found")
         );
 
+        // Matching tasks are reported as candidates
+        match locate_task(task_dir, "task-") {
+            Err(Error::AmbiguousId { id, candidates }) => {
+                assert_eq!(id, "task-");
+                assert_eq!(candidates, vec!["task-abc", "task-def"]);
+            }
+            _ => panic!("Should have failed with an ambiguous ID"),
+        }
+
         // Test error when no tasks match
         let file = locate_task(task_dir, "nonexistent");
         assert!(file.is_err());
         assert!(file.unwrap_err().to_string().contains("No task found"));
+        assert!(matches!(
+            locate_task(task_dir, "nonexistent"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_migrate_tasks() {
+        let temp_dir = tempdir().unwrap();
+        let task_dir = temp_dir.path();
+
+        // Write tasks without a schema version, as older versions did
+        for id in ["task-1", "task-2"] {
+            let task = create_test_task(id);
+            fs::write(
+                task_dir.join(format!("{id}.toml")),
+                toml::to_string(&task).unwrap(),
+            )
+            .unwrap();
+        }
+        // Tasks with the current schema version are left untouched
+        save_task(
+            task_dir,
+            &create_test_task("task-3"),
+            "create",
+            "Create task",
+        )
+        .unwrap();
+
+        let mut migrated = migrate_tasks(task_dir).unwrap();
+        migrated.sort();
+        assert_eq!(migrated, vec!["task-1", "task-2"]);
+        let contents = fs::read_to_string(task_dir.join("task-1.toml")).unwrap();
+        assert!(contents.starts_with(&format!("version = {SCHEMA_VERSION}")));
+
+        // Nothing left to migrate
+        assert!(migrate_tasks(task_dir).unwrap().is_empty());
+
+        // Files written by a newer version are reported
+        fs::write(
+            task_dir.join("task-4.toml"),
+            format!("version = {}\nid = \"task-4\"", SCHEMA_VERSION + 1),
+        )
+        .unwrap();
+        assert!(migrate_tasks(task_dir).is_err());
+        assert!(load_all_tasks(task_dir).is_err());
     }
 
     #[test]
@@ -377,9 +711,13 @@ mod tests {
         // but we'll just verify the file system effects
         let _ = delete_task(task_dir, &["delete-task-1"]);
 
-        // Verify the first file was deleted
+        // Verify the first file was moved to the trash
         let file_path = task_dir.join("delete-task-1.toml");
         assert!(!file_path.exists());
+        let trashed = load_trashed_tasks(task_dir).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].id, "delete-task-1");
+        assert!(trashed[0].deleted_at.is_some());
 
         // Verify the second file still exists
         let file_path = task_dir.join("delete-task-2.toml");
@@ -394,7 +732,7 @@ mod tests {
         // Create an invalid TOML file
         let invalid_file_path = task_dir.join("invalid-task.toml");
         let invalid_content = "this is not valid TOML content";
-        let mut file = File::create(invalid_file_path).unwrap();
+        let mut file = File::create(&invalid_file_path).unwrap();
         file.write_all(invalid_content.as_bytes()).unwrap();
 
         // Create a valid TOML file
@@ -408,6 +746,21 @@ mod tests {
         let tasks = load_all_tasks(task_dir).unwrap();
         assert_eq!(tasks.len(), 1);
         assert_eq!(tasks[0].id, "valid-task");
+
+        // A task with a bad timestamp is reported along with the invalid file
+        let bad_time = toml_string.replace("valid-task", "bad-time").replacen(
+            &valid_task.created_at.to_rfc3339(),
+            "yesterday",
+            1,
+        );
+        fs::write(task_dir.join("bad-time.toml"), bad_time).unwrap();
+        let (tasks, invalid_files) = load_all_tasks_checked(task_dir).unwrap();
+        assert_eq!(tasks.len(), 1);
+        let paths = invalid_files
+            .iter()
+            .map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [task_dir.join("bad-time.toml"), invalid_file_path]);
     }
 
     #[test]
@@ -463,10 +816,13 @@ mod tests {
             scope: Some("test-scope".to_string()),
             task_type: Some("test-type".to_string()),
             status: TaskStatus::Todo,
-            created_at: "2023-01-01T12:00:00+00:00".to_string(),
-            updated_at: Some("2023-01-02T12:00:00+00:00".to_string()),
+            created_at: DateTime::parse_from_rfc3339("2023-01-01T12:00:00+00:00").unwrap(),
+            updated_at: Some(DateTime::parse_from_rfc3339("2023-01-02T12:00:00+00:00").unwrap()),
             completed_at: None,
             time_spent: Some(3600), // 1 hour in seconds
+            deleted_at: None,
+            snoozed_until: None,
+            annotations: Vec::new(),
         };
 
         // Serialize to TOML
@@ -0,0 +1,195 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::{Task, TaskStatus};
+use crate::{Error, Result};
+
+/// Length of the task IDs shown in lists
+///
+/// Inputs made of this many digits or more are ID prefixes rather than short
+/// numbers, since shown IDs may only contain digits.
+pub const SHORT_ID_LENGTH: usize = 8;
+
+/// Short numbers assigned to working tasks
+///
+/// The working set is recomputed each time tasks are listed and kept outside
+/// the tasks repository, so the numbers stay the same between two listings
+/// and never conflict between clones.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WorkingSet {
+    /// IDs of the working tasks, the task at index `i` has number `i + 1`
+    pub tasks: Vec<String>,
+}
+
+impl WorkingSet {
+    /// Number the tasks to do, oldest first
+    pub fn from_tasks(tasks: &[Task]) -> Self {
+        let mut working = tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Todo)
+            .collect::<Vec<_>>();
+        working.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+
+        Self {
+            tasks: working.into_iter().map(|task| task.id.clone()).collect(),
+        }
+    }
+
+    /// Get the ID of the task with the given number
+    pub fn task_id(&self, number: usize) -> Option<&str> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.tasks.get(index))
+            .map(String::as_str)
+    }
+
+    /// Get the number of the task with the given ID
+    pub fn number(&self, task_id: &str) -> Option<usize> {
+        self.tasks
+            .iter()
+            .position(|id| id == task_id)
+            .map(|index| index + 1)
+    }
+
+    /// Resolve a short number to the full task ID
+    ///
+    /// Anything that is not a number, or is as long as the IDs shown in lists,
+    /// is returned as is, to be matched as an ID prefix. Other numbers missing
+    /// from the working set are not found, rather than matching an unrelated
+    /// task whose ID starts with digits.
+    pub fn resolve<'a>(&'a self, task_id: &'a str) -> Result<&'a str> {
+        if task_id.is_empty() || !task_id.bytes().all(|b| b.is_ascii_digit()) {
+            return Ok(task_id);
+        }
+        if let Some(id) = task_id.parse().ok().and_then(|number| self.task_id(number)) {
+            return Ok(id);
+        }
+        if task_id.len() >= SHORT_ID_LENGTH {
+            return Ok(task_id);
+        }
+
+        Err(anyhow::Error::new(Error::NotFound(task_id.to_string()))
+            .context(format!(
+                "No working task numbered {task_id}, run 'list' to number tasks again"
+            ))
+            .into())
+    }
+}
+
+/// Save the working set
+pub fn save_working_set(file_path: &Path, working_set: &WorkingSet) -> Result<()> {
+    log::debug!("Saving working set to {}", file_path.display());
+
+    // Make sure the directory exists
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let toml_string = toml::to_string(working_set)?;
+    let mut file = File::create(file_path)?;
+    file.write_all(toml_string.as_bytes())?;
+
+    Ok(())
+}
+
+/// Load the working set, if it has been computed
+pub fn load_working_set(file_path: &Path) -> Result<Option<WorkingSet>> {
+    log::trace!("Checking for working set at {}", file_path.display());
+
+    if !file_path.exists() {
+        log::debug!("No working set file found");
+        return Ok(None);
+    }
+
+    let mut contents = String::new();
+    let mut file = File::open(file_path).context(format!(
+        "Failed to open working set file at {}",
+        file_path.display()
+    ))?;
+    file.read_to_string(&mut contents)?;
+
+    let working_set: WorkingSet =
+        toml::from_str(&contents).context("Failed to parse working set TOML")?;
+
+    Ok(Some(working_set))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::task::Priority;
+
+    fn create_task(id: &str, status: TaskStatus, age: i64) -> Task {
+        let mut task = Task::new(
+            id.to_string(),
+            format!("Task {id}"),
+            Priority::Normal,
+            None,
+            None,
+        );
+        task.status = status;
+        task.created_at = Local::now().fixed_offset() - Duration::minutes(age);
+        task
+    }
+
+    #[test]
+    fn test_working_set_numbers() {
+        let tasks = [
+            create_task("newest", TaskStatus::Todo, 1),
+            create_task("done", TaskStatus::Done, 3),
+            create_task("oldest", TaskStatus::Todo, 5),
+            create_task("aborted", TaskStatus::Aborted, 2),
+            create_task("middle", TaskStatus::Todo, 3),
+        ];
+
+        let working_set = WorkingSet::from_tasks(&tasks);
+        assert_eq!(working_set.tasks, vec!["oldest", "middle", "newest"]);
+
+        assert_eq!(working_set.task_id(1), Some("oldest"));
+        assert_eq!(working_set.task_id(3), Some("newest"));
+        assert_eq!(working_set.task_id(0), None);
+        assert_eq!(working_set.task_id(4), None);
+        assert_eq!(working_set.number("middle"), Some(2));
+        assert_eq!(working_set.number("done"), None);
+
+        // Only numbers of working tasks are resolved
+        assert_eq!(working_set.resolve("2").unwrap(), "middle");
+        assert_eq!(working_set.resolve("old").unwrap(), "old");
+        assert_eq!(working_set.resolve("7a").unwrap(), "7a");
+
+        // Numbers out of the working set are not matched as ID prefixes
+        for number in ["0", "4", "7", "9999999"] {
+            let err = working_set.resolve(number).unwrap_err();
+            assert!(matches!(err.root(), Error::NotFound(id) if id == number));
+            assert!(err.to_string().contains("run 'list'"));
+        }
+
+        // Unless they are as long as the IDs shown in lists
+        assert_eq!(working_set.resolve("12345678").unwrap(), "12345678");
+    }
+
+    #[test]
+    fn test_save_load_working_set() {
+        let temp_dir = tempdir().unwrap();
+        let working_set_file = temp_dir.path().join("working_set.toml");
+
+        assert!(load_working_set(&working_set_file).unwrap().is_none());
+
+        let working_set = WorkingSet {
+            tasks: vec!["task-1".to_string(), "task-2".to_string()],
+        };
+        save_working_set(&working_set_file, &working_set).unwrap();
+
+        let loaded = load_working_set(&working_set_file).unwrap();
+        assert_eq!(loaded, Some(working_set));
+    }
+}
@@ -5,7 +5,13 @@ use rutd_tui::app::TuiApp;
 
 fn main() -> ExitCode {
     // Get configuration from environment variables
-    let Ok(config) = Config::new().inspect_err(|e| eprintln!("Failed to load configuration: {e}"))
+    let Ok(config) = Config::new()
+        .and_then(|config| {
+            // Work in the current workspace
+            let workspace = config.workspace_name(None).to_string();
+            config.with_workspace(&workspace)
+        })
+        .inspect_err(|e| eprintln!("Failed to load configuration: {e}"))
     else {
         return ExitCode::FAILURE;
     };